@@ -1,3 +1,6 @@
+pub mod swap;
+pub mod trigger;
+
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 struct AsciiHeaderValue(::http::HeaderValue);
 impl AsciiHeaderValue {
@@ -5,7 +8,7 @@ impl AsciiHeaderValue {
         // SAFETY check for [`AsciiHeaderValue::as_str`]
         let bytes = value.as_bytes();
         for &b in bytes {
-            if !(b >= 32 && b < 127 || b == b'\t') {
+            if !((32..127).contains(&b) || b == b'\t') {
                 return None;
             }
         }
@@ -39,6 +42,7 @@ macro_rules! str_header {
             pub fn into_value(self) -> ::http::HeaderValue {
                 self.0.into_value()
             }
+            #[allow(clippy::should_implement_trait)]
             pub fn from_str(s: &str) -> Option<Self> {
                 crate::AsciiHeaderValue::from_str(s).map($name)
             }
@@ -68,6 +72,287 @@ macro_rules! str_header {
     };
 }
 
+/// Decodes a header value that htmx percent encoded with
+/// `encodeURIComponent`, which it does for values that are not valid header
+/// values (such as non ASCII user input), marking them with an
+/// `*-URI-AutoEncoded: true` header. `%` that are not followed by two hex
+/// digits are kept as is. Fails if the decoded bytes are not UTF-8.
+fn percent_decode(bytes: &[u8]) -> Option<String> {
+    fn hex(digit: u8) -> Option<u8> {
+        (digit as char).to_digit(16).map(|digit| digit as u8)
+    }
+
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut rest = bytes;
+    while let Some((&b, tail)) = rest.split_first() {
+        if let (b'%', [hi, lo, ..]) = (b, tail) {
+            if let (Some(hi), Some(lo)) = (hex(*hi), hex(*lo)) {
+                decoded.push(hi << 4 | lo);
+                rest = &tail[2..];
+                continue;
+            }
+        }
+        decoded.push(b);
+        rest = tail;
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Reads a header value that was sent as it is. Fails if it is not UTF-8.
+fn utf8(bytes: &[u8]) -> Option<String> {
+    String::from_utf8(bytes.to_vec()).ok()
+}
+
+/// Decodes only the percent encoded runs of a url that form non ASCII UTF-8,
+/// such as `%C3%A9`, along with raw UTF-8. Other escapes such as `%2F` and
+/// `%26` are kept as they are, as decoding them would change the meaning of
+/// the url. Fails if raw bytes are not UTF-8.
+fn percent_decode_non_ascii(bytes: &[u8]) -> Option<String> {
+    use std::fmt::Write;
+    fn high_byte(escape: &[u8]) -> Option<u8> {
+        let [b'%', hi, lo, ..] = *escape else {
+            return None;
+        };
+        let hex = |digit: u8| (digit as char).to_digit(16).map(|digit| digit as u8);
+        let byte = hex(hi)? << 4 | hex(lo)?;
+        (byte >= 0x80).then_some(byte)
+    }
+
+    let mut decoded = String::with_capacity(bytes.len());
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let mut run = Vec::new();
+        while let Some(byte) = high_byte(rest) {
+            run.push(byte);
+            rest = &rest[3..];
+        }
+        // decode the valid UTF-8 of the run, re-escaping any invalid bytes
+        let mut run = &run[..];
+        while !run.is_empty() {
+            let valid = match std::str::from_utf8(run) {
+                Ok(valid) => valid,
+                Err(e) => std::str::from_utf8(&run[..e.valid_up_to()]).unwrap(),
+            };
+            decoded.push_str(valid);
+            run = &run[valid.len()..];
+            if let Some((&invalid, tail)) = run.split_first() {
+                let _ = write!(decoded, "%{invalid:02X}");
+                run = tail;
+            }
+        }
+        // copy everything up to the next escape as it is
+        let end = (1..rest.len())
+            .find(|&i| rest[i] == b'%')
+            .unwrap_or(rest.len());
+        decoded.push_str(std::str::from_utf8(&rest[..end]).ok()?);
+        rest = &rest[end..];
+    }
+    Some(decoded)
+}
+
+/// Percent encodes every byte of `s` that is not visible ASCII, along with
+/// any listed in `also`.
+fn percent_encode(s: &str, also: &[u8]) -> ::http::HeaderValue {
+    use std::fmt::Write;
+    let mut encoded = String::with_capacity(s.len());
+    for &b in s.as_bytes() {
+        if (b'!'..=b'~').contains(&b) && !also.contains(&b) {
+            encoded.push(b as char);
+        } else {
+            let _ = write!(encoded, "%{b:02X}");
+        }
+    }
+    ::http::HeaderValue::from_str(&encoded).expect("Percent Encoded Value Is Visible ASCII")
+}
+
+/// Encodes text the way htmx does, as it is when it is a valid header value,
+/// otherwise percent encoded. Returns whether it was encoded, which is when
+/// the `*-URI-AutoEncoded` header must be sent alongside it.
+fn auto_encode(s: &str) -> (::http::HeaderValue, bool) {
+    // `HeaderValue::from_str` allows non ASCII, which browsers do not send
+    let value = AsciiHeaderValue::from_str(s).map(AsciiHeaderValue::into_value);
+    match value.and_then(AsciiHeaderValue::from_value) {
+        Some(value) => (value.into_value(), false),
+        None => (percent_encode(s, b"%"), true),
+    }
+}
+
+/// Encodes a url with its non ASCII characters percent encoded, the way
+/// browsers send it. Never needs the `*-URI-AutoEncoded` header.
+fn url_encode(s: &str) -> (::http::HeaderValue, bool) {
+    (percent_encode(s, b""), false)
+}
+
+/// A header carrying arbitrary text, such as user input or element ids.
+///
+/// htmx percent encodes the text when it is not a valid header value, and
+/// says so with a companion `*-URI-AutoEncoded: true` header. The
+/// [`Header`](::headers::Header) impl only sees the value, so it never
+/// decodes; use `from_headers` and `insert_into` to handle the companion.
+macro_rules! text_header {
+    ($name:ident, $n:ident = $s:expr, $auto:ident = $a:expr) => {
+        text_header!(@ $name, $n = $s, $auto = $a, crate::utf8, crate::auto_encode);
+    };
+    (@ $name:ident, $n:ident = $s:expr, $auto:ident = $a:expr, $decode:path, $encode:path) => {
+        #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(String);
+        pub static $n: ::headers::HeaderName = ::headers::HeaderName::from_static($s);
+        /// Sent as `true` by htmx when the header's value is percent encoded.
+        pub static $auto: ::headers::HeaderName = ::headers::HeaderName::from_static($a);
+        impl $name {
+            pub fn new(s: impl Into<String>) -> Self {
+                $name(s.into())
+            }
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+            pub fn into_string(self) -> String {
+                self.0
+            }
+            /// The value to send, which is percent encoded when the text is
+            /// not a valid header value. Use [`insert_into`](Self::insert_into)
+            /// to also send the `*-URI-AutoEncoded` header when it is.
+            pub fn into_value(self) -> ::http::HeaderValue {
+                $encode(&self.0).0
+            }
+            #[allow(clippy::should_implement_trait)]
+            pub fn from_str(s: &str) -> Option<Self> {
+                Some($name::new(s))
+            }
+            /// Reads the header from a request, percent decoding it only when
+            /// htmx sent the `*-URI-AutoEncoded: true` header with it.
+            pub fn from_headers(headers: &::http::HeaderMap) -> Option<Self> {
+                let mut values = headers.get_all(&$n).iter();
+                let value = values
+                    .next()
+                    .and_then(|one| values.next().is_none().then_some(one))?;
+                let auto_encoded = headers
+                    .get(&$auto)
+                    .map_or(false, |value| value.as_bytes() == b"true");
+                if auto_encoded {
+                    $decode(crate::percent_decode(value.as_bytes())?.as_bytes()).map($name)
+                } else {
+                    $decode(value.as_bytes()).map($name)
+                }
+            }
+            /// Inserts the header into `headers`, along with the
+            /// `*-URI-AutoEncoded` header when the value had to be encoded.
+            pub fn insert_into(self, headers: &mut ::http::HeaderMap) {
+                let (value, auto_encoded) = $encode(&self.0);
+                headers.insert(&$n, value);
+                if auto_encoded {
+                    headers.insert(&$auto, ::http::HeaderValue::from_static("true"));
+                } else {
+                    headers.remove(&$auto);
+                }
+            }
+        }
+        impl ::headers::Header for $name {
+            fn name() -> &'static http::HeaderName {
+                &$n
+            }
+            fn decode<'i, I>(values: &mut I) -> Result<Self, ::headers::Error>
+            where
+                Self: Sized,
+                I: Iterator<Item = &'i ::http::HeaderValue>,
+            {
+                values
+                    .next()
+                    .and_then(|one| values.next().is_none().then_some(one))
+                    .and_then(|one| $decode(one.as_bytes()))
+                    .map($name)
+                    .ok_or_else(::headers::Error::invalid)
+            }
+
+            fn encode<E: Extend<::http::HeaderValue>>(&self, values: &mut E) {
+                values.extend(std::iter::once(self.clone().into_value()))
+            }
+        }
+    };
+}
+
+/// A header holding a comma separated list, which may be split across several
+/// header lines. These are joined with `, ` when decoding, which is what htmx
+/// sees through `getResponseHeader`.
+macro_rules! list_header {
+    ($name:ident, $n:ident = $s:expr) => {
+        #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(crate::AsciiHeaderValue);
+        pub static $n: ::headers::HeaderName = ::headers::HeaderName::from_static($s);
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                self.0.as_str()
+            }
+            pub fn into_value(self) -> ::http::HeaderValue {
+                self.0.into_value()
+            }
+            #[allow(clippy::should_implement_trait)]
+            pub fn from_str(s: &str) -> Option<Self> {
+                crate::AsciiHeaderValue::from_str(s).map($name)
+            }
+            /// Builds a value from JSON, escaping non ASCII characters as
+            /// `\uXXXX` so it is a valid header value. Only escapes correctly
+            /// within JSON strings, so this is intended for the JSON form of
+            /// the header.
+            pub fn from_json(json: &str) -> Option<Self> {
+                $name::from_str(&crate::escape_json_non_ascii(json))
+            }
+        }
+        impl ::headers::Header for $name {
+            fn name() -> &'static http::HeaderName {
+                &$n
+            }
+            fn decode<'i, I>(values: &mut I) -> Result<Self, ::headers::Error>
+            where
+                Self: Sized,
+                I: Iterator<Item = &'i ::http::HeaderValue>,
+            {
+                let mut joined = String::new();
+                for value in values {
+                    let value = crate::AsciiHeaderValue::from_value(value.clone())
+                        .ok_or_else(::headers::Error::invalid)?;
+                    if !joined.is_empty() {
+                        joined.push_str(", ");
+                    }
+                    joined.push_str(value.as_str());
+                }
+                (!joined.is_empty())
+                    .then(|| $name::from_str(&joined))
+                    .flatten()
+                    .ok_or_else(::headers::Error::invalid)
+            }
+
+            fn encode<E: Extend<::http::HeaderValue>>(&self, values: &mut E) {
+                values.extend(std::iter::once(self.clone().into_value()))
+            }
+        }
+    };
+}
+
+/// A header holding a url, which adds [`from_url`](Self::from_url) to percent
+/// encode non ASCII urls.
+macro_rules! url_header {
+    ($name:ident, $n:ident = $s:expr) => {
+        str_header!($name, $n = $s);
+        impl $name {
+            /// Percent encodes any characters of `url` that are not visible
+            /// ASCII. Existing percent encoding is left as is.
+            pub fn from_url(url: &str) -> Self {
+                $name(crate::AsciiHeaderValue(crate::percent_encode(url, b"")))
+            }
+        }
+    };
+}
+
+/// A header holding a url sent by the browser, with its non ASCII characters
+/// decoded. The rest of its percent encoding is kept, so the url can still be
+/// parsed.
+macro_rules! url_text_header {
+    ($name:ident, $n:ident = $s:expr, $auto:ident = $a:expr) => {
+        text_header!(@ $name, $n = $s, $auto = $a, crate::percent_decode_non_ascii, crate::url_encode);
+    };
+}
+
 macro_rules! true_header {
     ($name:ident, $n:ident = $s:expr) => {
         #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -105,34 +390,449 @@ macro_rules! true_header {
 
 pub mod request {
     true_header!(HxBoosted, HX_BOOSTED = "hx-boosted");
-    str_header!(HxCurrentUrl, HX_CURRENT_URL = "hx-current-url");
+    url_text_header!(
+        HxCurrentUrl,
+        HX_CURRENT_URL = "hx-current-url",
+        HX_CURRENT_URL_URI_AUTOENCODED = "hx-current-url-uri-autoencoded"
+    );
     true_header!(
         HxHistoryRestoreRequest,
         HX_HISTORY_RESTORE_REQUEST = "hx-history-restore-request"
     );
-    str_header!(HxPrompt, HX_PROMPT = "hx-prompt");
+    text_header!(
+        HxPrompt,
+        HX_PROMPT = "hx-prompt",
+        HX_PROMPT_URI_AUTOENCODED = "hx-prompt-uri-autoencoded"
+    );
     true_header!(HxRequest, HX_REQUEST = "hx-request");
-    str_header!(HxTarget, HX_TARGET = "hx-target");
-    str_header!(HxTriggerName, HX_TRIGGER_NAME = "hx-trigger-name");
-    str_header!(HxTrigger, HX_TRIGGER = "hx-trigger");
+    str_header!(HxRequestType, HX_REQUEST_TYPE = "hx-request-type");
+    text_header!(
+        HxSource,
+        HX_SOURCE = "hx-source",
+        HX_SOURCE_URI_AUTOENCODED = "hx-source-uri-autoencoded"
+    );
+    text_header!(
+        HxTarget,
+        HX_TARGET = "hx-target",
+        HX_TARGET_URI_AUTOENCODED = "hx-target-uri-autoencoded"
+    );
+    text_header!(
+        HxTriggerName,
+        HX_TRIGGER_NAME = "hx-trigger-name",
+        HX_TRIGGER_NAME_URI_AUTOENCODED = "hx-trigger-name-uri-autoencoded"
+    );
+    text_header!(
+        HxTrigger,
+        HX_TRIGGER = "hx-trigger",
+        HX_TRIGGER_URI_AUTOENCODED = "hx-trigger-uri-autoencoded"
+    );
 }
 
 pub mod response {
-    str_header!(HxLocation, HX_LOCATION = "hx-location");
-    str_header!(HxPushUrl, HX_PUSH_URL = "hx-push-url");
-    str_header!(HxRedirect, HX_REDIRECT = "hx-redirect");
+    url_header!(HxLocation, HX_LOCATION = "hx-location");
+    impl HxLocation {
+        /// Builds the JSON form of `HX-Location`, which lets the server pick
+        /// the target, swap, and other options of the client side request
+        /// alongside the path.
+        ///
+        /// ```
+        /// use htmx_headers::response::{HxLocation, LocationContext};
+        /// let location = HxLocation::from_context(&LocationContext {
+        ///     path: "/todos",
+        ///     target: Some("#list"),
+        ///     ..Default::default()
+        /// })
+        /// .unwrap();
+        /// assert_eq!(location.as_str(), r##"{"path":"/todos","target":"#list"}"##);
+        /// ```
+        pub fn from_context(context: &LocationContext<'_>) -> Option<Self> {
+            let mut json = String::from("{");
+            crate::push_json_field(&mut json, "path", context.path);
+            let optional = [
+                ("source", context.source),
+                ("event", context.event),
+                ("handler", context.handler),
+                ("target", context.target),
+                ("swap", context.swap),
+                ("select", context.select),
+            ];
+            for (key, value) in optional {
+                if let Some(value) = value {
+                    json.push(',');
+                    crate::push_json_field(&mut json, key, value);
+                }
+            }
+            json.push('}');
+            HxLocation::from_str(&crate::escape_json_non_ascii(&json))
+        }
+    }
+
+    /// The options of the JSON form of `HX-Location`. See
+    /// [`HxLocation::from_context`].
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+    pub struct LocationContext<'a> {
+        pub path: &'a str,
+        pub source: Option<&'a str>,
+        pub event: Option<&'a str>,
+        pub handler: Option<&'a str>,
+        pub target: Option<&'a str>,
+        pub swap: Option<&'a str>,
+        pub select: Option<&'a str>,
+    }
+
+    url_header!(HxPushUrl, HX_PUSH_URL = "hx-push-url");
+    url_header!(HxRedirect, HX_REDIRECT = "hx-redirect");
     true_header!(HxRefresh, HX_REFRESH = "hx-refresh");
-    str_header!(HxReplaceUrl, HX_REPLACE_URL = "hx-replace-url");
+    url_header!(HxReplaceUrl, HX_REPLACE_URL = "hx-replace-url");
     str_header!(HxReswap, HX_RESWAP = "hx-reswap");
+    impl HxReswap {
+        pub fn from_swap(swap: &crate::swap::Swap) -> Option<Self> {
+            HxReswap::from_str(&swap.to_string())
+        }
+    }
     str_header!(HxRetarget, HX_RETARGET = "hx-retarget");
     str_header!(HxReselect, HX_RESELECT = "hx-reselect");
-    str_header!(HxTrigger, HX_TRIGGER = "hx-trigger");
-    str_header!(
+    list_header!(HxTrigger, HX_TRIGGER = "hx-trigger");
+    list_header!(
         HxTriggerAfterSettle,
         HX_TRIGGER_AFTER_SETTLE = "hx-trigger-after-settle"
     );
-    str_header!(
+    list_header!(
         HxTriggerAfterSwap,
         HX_TRIGGER_AFTER_SWAP = "hx-trigger-after-swap"
     );
 }
+
+/// Pushes `"key":"value"` onto a JSON object being built.
+fn push_json_field(json: &mut String, key: &str, value: &str) {
+    push_json_str(json, key);
+    json.push(':');
+    push_json_str(json, value);
+}
+
+/// Pushes `s` to `json` as a JSON string, escaping quotes, backslashes and
+/// control characters. Shared with hyperide's `hx-vals` builder, and not
+/// part of the API.
+#[doc(hidden)]
+pub fn push_json_str(json: &mut String, s: &str) {
+    use std::fmt::Write;
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+/// Escapes any non ASCII characters of JSON as `\uXXXX`, so the result is a
+/// valid header value.
+fn escape_json_non_ascii(json: &str) -> String {
+    use std::fmt::Write;
+    let mut escaped = String::with_capacity(json.len());
+    for c in json.chars() {
+        if (' '..='~').contains(&c) {
+            escaped.push(c);
+        } else {
+            let mut units = [0; 2];
+            for unit in c.encode_utf16(&mut units) {
+                let _ = write!(escaped, "\\u{unit:04x}");
+            }
+        }
+    }
+    escaped
+}
+
+/// Formats a duration the way htmx parses intervals.
+fn fmt_interval(interval: ::std::time::Duration) -> String {
+    match interval.as_millis() {
+        ms if ms > 0 && ms % 1000 == 0 => format!("{}s", ms / 1000),
+        ms => format!("{ms}ms"),
+    }
+}
+
+/// A version of htmx, used to record when a header was introduced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HtmxVersion {
+    pub major: u16,
+    pub minor: u16,
+    pub patch: u16,
+}
+impl HtmxVersion {
+    pub const fn new(major: u16, minor: u16, patch: u16) -> Self {
+        HtmxVersion {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+/// Whether a header is sent by htmx to the server, or by the server to htmx.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    Request,
+    Response,
+}
+
+/// An entry in [`HEADERS`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KnownHeader {
+    /// The lowercase header name.
+    pub name: &'static str,
+    pub direction: Direction,
+    /// The first version of htmx to send or understand the header.
+    pub since: HtmxVersion,
+    /// The lowercase name of the `*-URI-AutoEncoded` header htmx sends when
+    /// it had to percent encode a request header's value.
+    pub auto_encoded: Option<&'static str>,
+}
+impl KnownHeader {
+    pub fn header_name(&self) -> ::http::HeaderName {
+        ::http::HeaderName::from_static(self.name)
+    }
+}
+
+const fn known(name: &'static str, direction: Direction, since: HtmxVersion) -> KnownHeader {
+    KnownHeader {
+        name,
+        direction,
+        since,
+        auto_encoded: None,
+    }
+}
+
+const fn text(name: &'static str, since: HtmxVersion, auto_encoded: &'static str) -> KnownHeader {
+    KnownHeader {
+        auto_encoded: Some(auto_encoded),
+        ..known(name, Direction::Request, since)
+    }
+}
+
+/// Every header known to htmx, for example to generate
+/// `Access-Control-Allow-Headers` and `Access-Control-Expose-Headers`.
+///
+/// ```
+/// use htmx_headers::{headers_for, Direction, HtmxVersion};
+/// let expose = headers_for(Direction::Response, HtmxVersion::new(1, 9, 4))
+///     .map(|header| header.name)
+///     .collect::<Vec<_>>()
+///     .join(", ");
+/// assert!(expose.contains("hx-reselect"));
+/// ```
+pub const HEADERS: &[KnownHeader] = {
+    use Direction::{Request, Response};
+    const V1_0: HtmxVersion = HtmxVersion::new(1, 0, 0);
+    &[
+        known("hx-boosted", Request, HtmxVersion::new(1, 6, 0)),
+        text("hx-current-url", V1_0, "hx-current-url-uri-autoencoded"),
+        known("hx-history-restore-request", Request, V1_0),
+        text("hx-prompt", V1_0, "hx-prompt-uri-autoencoded"),
+        known("hx-request", Request, V1_0),
+        known("hx-request-type", Request, HtmxVersion::new(4, 0, 0)),
+        text(
+            "hx-source",
+            HtmxVersion::new(4, 0, 0),
+            "hx-source-uri-autoencoded",
+        ),
+        text("hx-target", V1_0, "hx-target-uri-autoencoded"),
+        text("hx-trigger-name", V1_0, "hx-trigger-name-uri-autoencoded"),
+        text("hx-trigger", V1_0, "hx-trigger-uri-autoencoded"),
+        known("hx-location", Response, HtmxVersion::new(1, 8, 0)),
+        known("hx-push-url", Response, HtmxVersion::new(1, 8, 0)),
+        known("hx-redirect", Response, V1_0),
+        known("hx-refresh", Response, V1_0),
+        known("hx-replace-url", Response, HtmxVersion::new(1, 8, 0)),
+        known("hx-reswap", Response, HtmxVersion::new(1, 8, 2)),
+        known("hx-retarget", Response, HtmxVersion::new(1, 6, 1)),
+        known("hx-reselect", Response, HtmxVersion::new(1, 9, 3)),
+        known("hx-trigger", Response, V1_0),
+        known("hx-trigger-after-settle", Response, V1_0),
+        known("hx-trigger-after-swap", Response, V1_0),
+    ]
+};
+
+/// The headers in [`HEADERS`] going in `direction` that are understood by
+/// `version` of htmx.
+pub fn headers_for(
+    direction: Direction,
+    version: HtmxVersion,
+) -> impl Iterator<Item = &'static KnownHeader> {
+    HEADERS
+        .iter()
+        .filter(move |header| header.direction == direction && header.since <= version)
+}
+
+#[cfg(test)]
+mod tests {
+    use ::headers::Header;
+    use ::http::{HeaderMap, HeaderValue};
+
+    use crate::{request, response};
+
+    fn decode<H: Header>(values: &[&[u8]]) -> Result<H, ::headers::Error> {
+        let values = values
+            .iter()
+            .map(|value| HeaderValue::from_bytes(value).unwrap())
+            .collect::<Vec<_>>();
+        H::decode(&mut values.iter())
+    }
+
+    fn headers(pairs: &[(&::headers::HeaderName, &str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|&(name, value)| (name.clone(), HeaderValue::from_str(value).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn text_header_decodes_when_auto_encoded() {
+        let map = headers(&[
+            (&request::HX_PROMPT, "caf%C3%A9%20au%20lait%2C%20100%25"),
+            (&request::HX_PROMPT_URI_AUTOENCODED, "true"),
+        ]);
+        let prompt = request::HxPrompt::from_headers(&map).unwrap();
+        assert_eq!(prompt.as_str(), "café au lait, 100%");
+    }
+
+    #[test]
+    fn text_header_keeps_escapes_when_not_auto_encoded() {
+        let map = headers(&[(&request::HX_PROMPT, "caf%C3%A9 50%25")]);
+        let prompt = request::HxPrompt::from_headers(&map).unwrap();
+        assert_eq!(prompt.as_str(), "caf%C3%A9 50%25");
+        let prompt = decode::<request::HxPrompt>(&[b"caf%C3%A9 50%25"]).unwrap();
+        assert_eq!(prompt.as_str(), "caf%C3%A9 50%25");
+    }
+
+    #[test]
+    fn text_header_decodes_raw_utf8() {
+        let prompt = decode::<request::HxPrompt>(&["café".as_bytes()]).unwrap();
+        assert_eq!(prompt.as_str(), "café");
+    }
+
+    #[test]
+    fn text_header_keeps_lone_percent() {
+        let prompt = decode::<request::HxPrompt>(&[b"100% sure"]).unwrap();
+        assert_eq!(prompt.as_str(), "100% sure");
+        let prompt = decode::<request::HxPrompt>(&[b"trailing %A"]).unwrap();
+        assert_eq!(prompt.as_str(), "trailing %A");
+    }
+
+    #[test]
+    fn text_header_rejects_missing_value() {
+        assert!(decode::<request::HxTriggerName>(&[]).is_err());
+    }
+
+    #[test]
+    fn text_header_rejects_multiple_values() {
+        assert!(decode::<request::HxTriggerName>(&[b"a", b"b"]).is_err());
+    }
+
+    #[test]
+    fn text_header_rejects_invalid_utf8() {
+        assert!(decode::<request::HxPrompt>(&[b"\xff"]).is_err());
+        let map = headers(&[
+            (&request::HX_PROMPT, "%FF%FE"),
+            (&request::HX_PROMPT_URI_AUTOENCODED, "true"),
+        ]);
+        assert!(request::HxPrompt::from_headers(&map).is_none());
+    }
+
+    #[test]
+    fn text_header_round_trips() {
+        for text in ["plain 100%", "ünï cödé, 100%"] {
+            let original = request::HxPrompt::new(text);
+            let mut map = HeaderMap::new();
+            original.clone().insert_into(&mut map);
+            assert!(map[&request::HX_PROMPT].to_str().is_ok());
+            assert_eq!(
+                map.contains_key(&request::HX_PROMPT_URI_AUTOENCODED),
+                !text.is_ascii()
+            );
+            let decoded = request::HxPrompt::from_headers(&map).unwrap();
+            assert_eq!(decoded, original);
+        }
+    }
+
+    #[test]
+    fn url_text_header_decodes_when_auto_encoded() {
+        let map = headers(&[
+            (
+                &request::HX_CURRENT_URL,
+                "https%3A%2F%2Fexample.com%2Fcaf%C3%A9%3Fq%3Da%2526b",
+            ),
+            (&request::HX_CURRENT_URL_URI_AUTOENCODED, "true"),
+        ]);
+        let url = request::HxCurrentUrl::from_headers(&map).unwrap();
+        assert_eq!(url.as_str(), "https://example.com/café?q=a%26b");
+    }
+
+    #[test]
+    fn url_text_header_only_decodes_non_ascii() {
+        let url = decode::<request::HxCurrentUrl>(&[
+            b"https://example.com/caf%C3%A9?next=%2Fa%3Fb%3Dc&q=a%26b%20c",
+        ])
+        .unwrap();
+        assert_eq!(
+            url.as_str(),
+            "https://example.com/café?next=%2Fa%3Fb%3Dc&q=a%26b%20c"
+        );
+    }
+
+    #[test]
+    fn url_text_header_keeps_invalid_escapes() {
+        let url = decode::<request::HxCurrentUrl>(&[b"/%FF%C3%A9%e2%82?q=100%"]).unwrap();
+        assert_eq!(url.as_str(), "/%FFé%E2%82?q=100%");
+        let url = decode::<request::HxCurrentUrl>(&["/café".as_bytes()]).unwrap();
+        assert_eq!(url.as_str(), "/café");
+    }
+
+    #[test]
+    fn url_text_header_round_trips() {
+        let original = request::HxCurrentUrl::new("https://example.com/ünï?q=a%26b");
+        let value = original.clone().into_value();
+        assert_eq!(value, "https://example.com/%C3%BCn%C3%AF?q=a%26b");
+        let decoded = decode::<request::HxCurrentUrl>(&[value.as_bytes()]).unwrap();
+        assert_eq!(decoded, original);
+    }
+
+    #[test]
+    fn str_header_rejects_non_ascii() {
+        assert!(decode::<request::HxRequestType>(&["pärtial".as_bytes()]).is_err());
+    }
+
+    #[test]
+    fn list_header_joins_values() {
+        let trigger = decode::<response::HxTrigger>(&[b"first", b"second"]).unwrap();
+        assert_eq!(trigger.as_str(), "first, second");
+    }
+
+    #[test]
+    fn list_header_rejects_missing_value() {
+        assert!(decode::<response::HxTrigger>(&[]).is_err());
+    }
+
+    #[test]
+    fn pushes_json_strings() {
+        let mut json = String::new();
+        crate::push_json_str(&mut json, "a \"quote\" \\ café\n\u{7f}");
+        assert_eq!(json, r#""a \"quote\" \\ café\u000a\u007f""#);
+    }
+
+    #[test]
+    fn list_header_escapes_json() {
+        let trigger = response::HxTrigger::from_json(r#"{"flash":"Café ☕"}"#).unwrap();
+        assert_eq!(trigger.as_str(), r#"{"flash":"Caf\u00e9 \u2615"}"#);
+    }
+
+    #[test]
+    fn url_header_percent_encodes() {
+        let redirect = response::HxRedirect::from_url("/wiki/Café?q=a%20b");
+        assert_eq!(redirect.as_str(), "/wiki/Caf%C3%A9?q=a%20b");
+    }
+}
@@ -0,0 +1,236 @@
+//! The [`hx-swap`](https://htmx.org/attributes/hx-swap/) syntax, shared by the
+//! `hx-swap` attribute and the `HX-Reswap` response header.
+
+use std::{fmt, time::Duration};
+
+use crate::fmt_interval;
+
+/// How content is swapped into the target.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SwapStyle {
+    #[default]
+    InnerHtml,
+    OuterHtml,
+    BeforeBegin,
+    AfterBegin,
+    BeforeEnd,
+    AfterEnd,
+    Delete,
+    None,
+}
+impl SwapStyle {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SwapStyle::InnerHtml => "innerHTML",
+            SwapStyle::OuterHtml => "outerHTML",
+            SwapStyle::BeforeBegin => "beforebegin",
+            SwapStyle::AfterBegin => "afterbegin",
+            SwapStyle::BeforeEnd => "beforeend",
+            SwapStyle::AfterEnd => "afterend",
+            SwapStyle::Delete => "delete",
+            SwapStyle::None => "none",
+        }
+    }
+}
+
+/// Which end of an element to scroll to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ScrollPosition {
+    Top,
+    Bottom,
+}
+impl ScrollPosition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScrollPosition::Top => "top",
+            ScrollPosition::Bottom => "bottom",
+        }
+    }
+}
+
+/// A swap style along with its modifiers.
+///
+/// ```
+/// use std::time::Duration;
+/// use htmx_headers::swap::{ScrollPosition, Swap, SwapStyle};
+/// let swap = Swap::new(SwapStyle::OuterHtml)
+///     .settle(Duration::from_millis(100))
+///     .scroll_window(ScrollPosition::Top);
+/// assert_eq!(swap.to_string(), "outerHTML settle:100ms scroll:window:top");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Swap {
+    style: SwapStyle,
+    transition: Option<bool>,
+    swap: Option<Duration>,
+    settle: Option<Duration>,
+    ignore_title: Option<bool>,
+    scroll: Option<(Option<String>, ScrollPosition)>,
+    show: Option<(Option<String>, ScrollPosition)>,
+    show_none: bool,
+    focus_scroll: Option<bool>,
+}
+impl Swap {
+    pub fn new(style: SwapStyle) -> Swap {
+        Swap {
+            style,
+            ..Default::default()
+        }
+    }
+
+    /// Whether to use the view transition API.
+    pub fn transition(mut self, transition: bool) -> Swap {
+        self.transition = Some(transition);
+        self
+    }
+
+    /// The delay between receiving the response and swapping.
+    pub fn swap(mut self, delay: Duration) -> Swap {
+        self.swap = Some(delay);
+        self
+    }
+
+    /// The delay between swapping and settling.
+    pub fn settle(mut self, delay: Duration) -> Swap {
+        self.settle = Some(delay);
+        self
+    }
+
+    /// Whether to ignore any `<title>` in the response.
+    pub fn ignore_title(mut self, ignore: bool) -> Swap {
+        self.ignore_title = Some(ignore);
+        self
+    }
+
+    /// Scroll the target to `position`.
+    pub fn scroll(mut self, position: ScrollPosition) -> Swap {
+        self.scroll = Some((None, position));
+        self
+    }
+
+    /// Scroll the element matching `selector` to `position`.
+    pub fn scroll_to(mut self, selector: impl Into<String>, position: ScrollPosition) -> Swap {
+        self.scroll = Some((Some(selector.into()), position));
+        self
+    }
+
+    /// Scroll the window to `position`.
+    pub fn scroll_window(self, position: ScrollPosition) -> Swap {
+        self.scroll_to("window", position)
+    }
+
+    /// Scroll the target into view, aligned to `position`.
+    pub fn show(mut self, position: ScrollPosition) -> Swap {
+        self.show = Some((None, position));
+        self.show_none = false;
+        self
+    }
+
+    /// Scroll the element matching `selector` into view, aligned to
+    /// `position`.
+    pub fn show_element(mut self, selector: impl Into<String>, position: ScrollPosition) -> Swap {
+        self.show = Some((Some(selector.into()), position));
+        self.show_none = false;
+        self
+    }
+
+    /// Scroll the window into view, aligned to `position`.
+    pub fn show_window(self, position: ScrollPosition) -> Swap {
+        self.show_element("window", position)
+    }
+
+    /// Disable the default of showing the target after a boosted swap.
+    pub fn show_none(mut self) -> Swap {
+        self.show = None;
+        self.show_none = true;
+        self
+    }
+
+    /// Whether to scroll focused inputs into view.
+    pub fn focus_scroll(mut self, focus_scroll: bool) -> Swap {
+        self.focus_scroll = Some(focus_scroll);
+        self
+    }
+}
+impl From<SwapStyle> for Swap {
+    fn from(style: SwapStyle) -> Self {
+        Swap::new(style)
+    }
+}
+impl fmt::Display for Swap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.style.as_str())?;
+        if let Some(transition) = self.transition {
+            write!(f, " transition:{transition}")?;
+        }
+        if let Some(swap) = self.swap {
+            write!(f, " swap:{}", fmt_interval(swap))?;
+        }
+        if let Some(settle) = self.settle {
+            write!(f, " settle:{}", fmt_interval(settle))?;
+        }
+        if let Some(ignore_title) = self.ignore_title {
+            write!(f, " ignoreTitle:{ignore_title}")?;
+        }
+        for (modifier, value) in [("scroll", &self.scroll), ("show", &self.show)] {
+            match value {
+                Some((Some(selector), position)) => {
+                    write!(f, " {modifier}:{selector}:{}", position.as_str())?
+                }
+                Some((None, position)) => write!(f, " {modifier}:{}", position.as_str())?,
+                None => {}
+            }
+        }
+        if self.show_none {
+            f.write_str(" show:none")?;
+        }
+        if let Some(focus_scroll) = self.focus_scroll {
+            write!(f, " focus-scroll:{focus_scroll}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn writes_every_modifier() {
+        let swap = Swap::new(SwapStyle::BeforeEnd)
+            .transition(true)
+            .swap(Duration::from_secs(1))
+            .settle(Duration::from_millis(1500))
+            .ignore_title(false)
+            .scroll(ScrollPosition::Bottom)
+            .show_element("#top", ScrollPosition::Top)
+            .focus_scroll(false);
+        assert_eq!(
+            swap.to_string(),
+            "beforeend transition:true swap:1s settle:1500ms ignoreTitle:false \
+             scroll:bottom show:#top:top focus-scroll:false"
+        );
+    }
+
+    #[test]
+    fn writes_selectors_as_they_are() {
+        let swap = Swap::new(SwapStyle::InnerHtml).scroll_to("li:last-child", ScrollPosition::Top);
+        assert_eq!(swap.to_string(), "innerHTML scroll:li:last-child:top");
+    }
+
+    #[test]
+    fn show_none_replaces_show() {
+        let swap = Swap::default().show_window(ScrollPosition::Top).show_none();
+        assert_eq!(swap.to_string(), "innerHTML show:none");
+        let swap = Swap::default().show_none().show(ScrollPosition::Bottom);
+        assert_eq!(swap.to_string(), "innerHTML show:bottom");
+    }
+
+    #[test]
+    fn writes_zero_intervals_in_milliseconds() {
+        let swap = Swap::new(SwapStyle::None).swap(Duration::ZERO);
+        assert_eq!(swap.to_string(), "none swap:0ms");
+    }
+}
@@ -0,0 +1,244 @@
+//! The [`hx-trigger`](https://htmx.org/attributes/hx-trigger/) syntax.
+
+use std::{fmt, time::Duration};
+
+use crate::fmt_interval;
+
+/// How events that arrive while a request is in flight are queued.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Queue {
+    First,
+    Last,
+    All,
+    None,
+}
+impl Queue {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Queue::First => "first",
+            Queue::Last => "last",
+            Queue::All => "all",
+            Queue::None => "none",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Modifier {
+    Once,
+    Changed,
+    Delay(Duration),
+    Throttle(Duration),
+    From(String),
+    Target(String),
+    Consume,
+    Queue(Queue),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Source {
+    Event(String),
+    Every(Duration),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct TriggerEvent {
+    source: Source,
+    filter: Option<String>,
+    modifiers: Vec<Modifier>,
+}
+
+/// Starts building a [`Trigger`], which must have at least one event.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TriggerBuilder;
+impl TriggerBuilder {
+    /// Trigger on the event called `name`.
+    pub fn event(self, name: impl Into<String>) -> Trigger {
+        Trigger { events: Vec::new() }.event(name)
+    }
+
+    /// Trigger by polling every `interval`.
+    pub fn every(self, interval: Duration) -> Trigger {
+        Trigger { events: Vec::new() }.every(interval)
+    }
+}
+
+/// One or more events that trigger a request, each with their own filter and
+/// modifiers. Filters and modifiers apply to the most recently added event.
+///
+/// ```
+/// use std::time::Duration;
+/// use htmx_headers::trigger::Trigger;
+/// let trigger = Trigger::builder()
+///     .event("keyup")
+///     .changed()
+///     .delay(Duration::from_millis(500))
+///     .event("search");
+/// assert_eq!(trigger.to_string(), "keyup changed delay:500ms, search");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Trigger {
+    events: Vec<TriggerEvent>,
+}
+impl Trigger {
+    pub fn builder() -> TriggerBuilder {
+        TriggerBuilder
+    }
+
+    /// Also trigger on the event called `name`.
+    pub fn event(self, name: impl Into<String>) -> Trigger {
+        self.push(Source::Event(name.into()))
+    }
+
+    /// Also trigger by polling every `interval`.
+    pub fn every(self, interval: Duration) -> Trigger {
+        self.push(Source::Every(interval))
+    }
+
+    fn push(mut self, source: Source) -> Trigger {
+        self.events.push(TriggerEvent {
+            source,
+            filter: None,
+            modifiers: Vec::new(),
+        });
+        self
+    }
+
+    fn last(&mut self) -> &mut TriggerEvent {
+        // A trigger can only be created through [`TriggerBuilder`], which
+        // always adds an event
+        self.events.last_mut().expect("Trigger Always Has An Event")
+    }
+
+    fn modifier(mut self, modifier: Modifier) -> Trigger {
+        self.last().modifiers.push(modifier);
+        self
+    }
+
+    /// Only trigger when the javascript expression `filter` is true, written
+    /// inside `[]`.
+    pub fn filter(mut self, filter: impl Into<String>) -> Trigger {
+        self.last().filter = Some(filter.into());
+        self
+    }
+
+    /// Only trigger once.
+    pub fn once(self) -> Trigger {
+        self.modifier(Modifier::Once)
+    }
+
+    /// Only trigger if the value of the element has changed.
+    pub fn changed(self) -> Trigger {
+        self.modifier(Modifier::Changed)
+    }
+
+    /// Wait for `delay` without another event before triggering.
+    pub fn delay(self, delay: Duration) -> Trigger {
+        self.modifier(Modifier::Delay(delay))
+    }
+
+    /// Trigger at most once every `throttle`.
+    pub fn throttle(self, throttle: Duration) -> Trigger {
+        self.modifier(Modifier::Throttle(throttle))
+    }
+
+    /// Listen for the event on the elements matching `selector`.
+    pub fn from(self, selector: impl Into<String>) -> Trigger {
+        self.modifier(Modifier::From(selector.into()))
+    }
+
+    /// Only trigger if the event target matches `selector`.
+    pub fn target(self, selector: impl Into<String>) -> Trigger {
+        self.modifier(Modifier::Target(selector.into()))
+    }
+
+    /// Stop the event from triggering requests on parent elements.
+    pub fn consume(self) -> Trigger {
+        self.modifier(Modifier::Consume)
+    }
+
+    /// How to queue events that arrive while a request is in flight.
+    pub fn queue(self, queue: Queue) -> Trigger {
+        self.modifier(Modifier::Queue(queue))
+    }
+}
+impl fmt::Display for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, event) in self.events.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            match &event.source {
+                Source::Event(name) => f.write_str(name)?,
+                Source::Every(interval) => write!(f, "every {}", fmt_interval(*interval))?,
+            }
+            if let Some(filter) = &event.filter {
+                write!(f, "[{filter}]")?;
+            }
+            for modifier in &event.modifiers {
+                match modifier {
+                    Modifier::Once => f.write_str(" once")?,
+                    Modifier::Changed => f.write_str(" changed")?,
+                    Modifier::Delay(delay) => write!(f, " delay:{}", fmt_interval(*delay))?,
+                    Modifier::Throttle(throttle) => {
+                        write!(f, " throttle:{}", fmt_interval(*throttle))?
+                    }
+                    Modifier::From(selector) => write!(f, " from:{selector}")?,
+                    Modifier::Target(selector) => write!(f, " target:{selector}")?,
+                    Modifier::Consume => f.write_str(" consume")?,
+                    Modifier::Queue(queue) => write!(f, " queue:{}", queue.as_str())?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn writes_every_modifier() {
+        let trigger = Trigger::builder()
+            .event("click")
+            .once()
+            .throttle(Duration::from_secs(2))
+            .from("closest form")
+            .target("#button")
+            .consume()
+            .queue(Queue::Last);
+        assert_eq!(
+            trigger.to_string(),
+            "click once throttle:2s from:closest form target:#button consume queue:last"
+        );
+    }
+
+    #[test]
+    fn writes_filters_before_modifiers() {
+        let trigger = Trigger::builder()
+            .event("keyup")
+            .delay(Duration::from_millis(250))
+            .filter("key=='Enter' && !event.shiftKey");
+        assert_eq!(
+            trigger.to_string(),
+            "keyup[key=='Enter' && !event.shiftKey] delay:250ms"
+        );
+    }
+
+    #[test]
+    fn separates_events_with_commas() {
+        let trigger = Trigger::builder()
+            .every(Duration::from_secs(30))
+            .filter("document.visibilityState === \"visible\"")
+            .event("refresh")
+            .from("body")
+            .queue(Queue::None);
+        assert_eq!(
+            trigger.to_string(),
+            r#"every 30s[document.visibilityState === "visible"], refresh from:body queue:none"#
+        );
+    }
+}
@@ -0,0 +1,684 @@
+//! A checker for a subset of the [hyperscript](https://hyperscript.org/)
+//! grammar, used to reject literal `_` attributes which would fail to parse in
+//! the browser.
+//!
+//! Features (`on`, `init`, `def`, `behavior`, ...) and the commands starting
+//! each statement are checked, along with strings, query literals (`<input/>`)
+//! and brackets. Expressions are only checked for balanced brackets, and
+//! anything ambiguous is accepted, so valid hyperscript is never rejected.
+
+/// Why a script failed to parse, and the byte offset in the script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub offset: usize,
+    pub message: String,
+}
+impl Error {
+    fn new(offset: usize, message: impl Into<String>) -> Error {
+        Error {
+            offset,
+            message: message.into(),
+        }
+    }
+
+    /// The line and column of the error, both starting from 1.
+    pub fn line_column(&self, source: &str) -> (usize, usize) {
+        let before = &source[..self.offset.min(source.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+        (line, column)
+    }
+}
+
+const COMMANDS: &[&str] = &[
+    "add",
+    "append",
+    "async",
+    "beep",
+    "break",
+    "call",
+    "close",
+    "continue",
+    "decrement",
+    "default",
+    "exit",
+    "fetch",
+    "for",
+    "get",
+    "go",
+    "halt",
+    "hide",
+    "if",
+    "increment",
+    "js",
+    "log",
+    "make",
+    "measure",
+    "open",
+    "pick",
+    "put",
+    "remove",
+    "render",
+    "repeat",
+    "return",
+    "scroll",
+    "send",
+    "set",
+    "settle",
+    "show",
+    "take",
+    "tell",
+    "throw",
+    "toggle",
+    "transition",
+    "trigger",
+    "wait",
+];
+
+/// Commands which contain a list of commands, closed by `end`.
+const BLOCKS: &[&str] = &["if", "repeat", "for", "tell"];
+
+/// Features which start a new feature when at the start of a line.
+const FEATURES: &[&str] = &["on", "init", "def", "behavior", "install"];
+
+/// Features with their own grammars, which are not checked.
+const UNCHECKED_FEATURES: &[&str] = &["worker", "socket", "eventsource", "bind", "live", "when"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Ident,
+    Number,
+    Str,
+    Class,
+    Id,
+    Query,
+    Attr,
+    Punct(char),
+    Newline,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    kind: Kind,
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+/// Checks that `source` is valid hyperscript.
+pub fn check(source: &str) -> Result<(), Error> {
+    let tokens = lex(source)?;
+    Parser {
+        tokens,
+        pos: 0,
+        end: source.len(),
+    }
+    .parse_features()
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || c == '$'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '$'
+}
+
+fn lex(source: &str) -> Result<Vec<Token<'_>>, Error> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut brackets: Vec<(char, usize)> = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    let rest = |i: usize| &source[i..];
+
+    while let Some((start, c)) = chars.next() {
+        let take_while = |chars: &mut std::iter::Peekable<std::str::CharIndices>,
+                          f: &dyn Fn(char) -> bool| {
+            while chars.next_if(|&(_, c)| f(c)).is_some() {}
+            chars.peek().map_or(source.len(), |&(i, _)| i)
+        };
+        let after_space = tokens.last().is_none_or(|t| t.end < start)
+            || matches!(
+                tokens.last().map(|t| t.kind),
+                Some(Kind::Punct('(' | '[' | '{' | ',')) | Some(Kind::Newline)
+            );
+        // only something which is a value can be followed by a possessive
+        let after_value = !after_space
+            && matches!(
+                tokens.last().map(|t| t.kind),
+                Some(Kind::Ident | Kind::Id | Kind::Query | Kind::Punct(')' | ']'))
+            );
+
+        let kind = match c {
+            '\n' => {
+                if tokens.last().is_none_or(|t| t.kind == Kind::Newline) {
+                    continue;
+                }
+                Kind::Newline
+            }
+            c if c.is_whitespace() => continue,
+            '-' if rest(start).starts_with("--") => {
+                take_while(&mut chars, &|c| c != '\n');
+                continue;
+            }
+            '/' if rest(start).starts_with("//") => {
+                take_while(&mut chars, &|c| c != '\n');
+                continue;
+            }
+            // a possessive, such as `the event's target`
+            '\'' if after_value
+                && rest(start).starts_with("'s")
+                && !rest(start)[2..].starts_with(is_ident_char) =>
+            {
+                chars.next();
+                Kind::Punct('\'')
+            }
+            '\'' | '"' | '`' => {
+                let mut escaped = false;
+                let mut closed = false;
+                for (_, s) in chars.by_ref() {
+                    match s {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        s if s == c => {
+                            closed = true;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                if !closed {
+                    return Err(Error::new(start, "unterminated string"));
+                }
+                Kind::Str
+            }
+            c if c.is_ascii_digit() => {
+                take_while(&mut chars, &|c| c.is_ascii_digit() || c == '.');
+                Kind::Number
+            }
+            c if is_ident_start(c) => {
+                take_while(&mut chars, &is_ident_char);
+                Kind::Ident
+            }
+            '.' if after_space
+                && chars
+                    .peek()
+                    .is_some_and(|&(_, c)| is_ident_start(c) || c == '-') =>
+            {
+                take_while(&mut chars, &|c| is_ident_char(c) || c == '-');
+                Kind::Class
+            }
+            '#' if chars.peek().is_some_and(|&(_, c)| is_ident_char(c)) => {
+                take_while(&mut chars, &|c| is_ident_char(c) || c == '-');
+                Kind::Id
+            }
+            '@' => {
+                take_while(&mut chars, &|c| is_ident_char(c) || c == '-' || c == ':');
+                Kind::Attr
+            }
+            '<' if chars
+                .peek()
+                .is_some_and(|&(_, c)| c.is_ascii_alphabetic() || ".#*[:".contains(c)) =>
+            {
+                let Some(len) = rest(start).find("/>") else {
+                    return Err(Error::new(
+                        start,
+                        "unterminated query literal, expected it to end with `/>`",
+                    ));
+                };
+                let end = start + len + 2;
+                while chars.next_if(|&(i, _)| i < end).is_some() {}
+                Kind::Query
+            }
+            c => {
+                match c {
+                    '(' | '[' | '{' => brackets.push((c, start)),
+                    ')' | ']' | '}' => {
+                        let open = match c {
+                            ')' => '(',
+                            ']' => '[',
+                            _ => '{',
+                        };
+                        match brackets.pop() {
+                            Some((o, _)) if o == open => {}
+                            _ => return Err(Error::new(start, format!("unexpected `{c}`"))),
+                        }
+                    }
+                    _ => {}
+                }
+                Kind::Punct(c)
+            }
+        };
+        let end = chars.peek().map_or(source.len(), |&(i, _)| i);
+        tokens.push(Token {
+            kind,
+            text: &source[start..end],
+            start,
+            end,
+        });
+    }
+
+    if let Some((open, offset)) = brackets.pop() {
+        return Err(Error::new(offset, format!("unclosed `{open}`")));
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    end: usize,
+}
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn peek_ident(&self) -> Option<&'a str> {
+        self.peek()
+            .filter(|t| t.kind == Kind::Ident)
+            .map(|t| t.text)
+    }
+
+    fn bump(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+        self.pos += 1;
+        token
+    }
+
+    fn eat_ident(&mut self, ident: &str) -> bool {
+        if self.peek_ident() == Some(ident) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn at_line_start(&self) -> bool {
+        self.pos == 0 || self.tokens[self.pos - 1].kind == Kind::Newline
+    }
+
+    fn skip_newlines(&mut self) {
+        while self.peek().is_some_and(|t| t.kind == Kind::Newline) {
+            self.pos += 1;
+        }
+    }
+
+    fn error_here(&self, message: impl Into<String>) -> Error {
+        let offset = self.peek().map_or(self.end, |t| t.start);
+        Error::new(offset, message)
+    }
+
+    fn found(&self) -> String {
+        match self.peek() {
+            Some(t) if t.kind == Kind::Newline => "a new line".into(),
+            Some(t) => format!("`{}`", t.text),
+            None => "the end of the script".into(),
+        }
+    }
+
+    /// Skips a bracketed group if the next token opens one. Brackets are
+    /// already known to be balanced.
+    fn skip_group(&mut self, open: char) -> bool {
+        if self.peek().map(|t| t.kind) != Some(Kind::Punct(open)) {
+            return false;
+        }
+        let mut depth = 0;
+        while let Some(token) = self.bump() {
+            match token.kind {
+                Kind::Punct('(' | '[' | '{') => depth += 1,
+                Kind::Punct(')' | ']' | '}') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        true
+    }
+
+    /// Skips the arguments of a command or the expression of a modifier,
+    /// stopping at anything which could start the next statement.
+    fn skip_expression(&mut self) {
+        while let Some(token) = self.peek() {
+            match token.kind {
+                Kind::Newline => return,
+                Kind::Punct('(' | '[' | '{') => {
+                    self.skip_group(token.text.chars().next().unwrap_or('('));
+                    continue;
+                }
+                Kind::Ident => match token.text {
+                    "then" | "end" | "else" | "otherwise" | "catch" | "finally" => return,
+                    // `for` is also used inside commands, such as `take .a for me`
+                    "for" => {}
+                    text if COMMANDS.contains(&text) => return,
+                    _ => {}
+                },
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn parse_features(&mut self) -> Result<(), Error> {
+        let mut behaviors = 0;
+        loop {
+            self.skip_newlines();
+            let Some(token) = self.peek() else {
+                return Ok(());
+            };
+            let feature = self.peek_ident().unwrap_or_default();
+            match feature {
+                "on" => {
+                    self.pos += 1;
+                    self.parse_on()?;
+                    self.parse_commands()?;
+                }
+                "init" => {
+                    self.pos += 1;
+                    self.eat_ident("immediately");
+                    self.parse_commands()?;
+                }
+                "def" => {
+                    self.pos += 1;
+                    self.parse_name("function name")?;
+                    self.skip_group('(');
+                    self.parse_commands()?;
+                }
+                "behavior" => {
+                    self.pos += 1;
+                    self.parse_name("behavior name")?;
+                    self.skip_group('(');
+                    behaviors += 1;
+                }
+                "install" => {
+                    self.pos += 1;
+                    self.parse_name("behavior name")?;
+                    self.skip_group('(');
+                }
+                "set" => {
+                    self.pos += 1;
+                    self.skip_expression();
+                }
+                "js" => {
+                    self.pos += 1;
+                    self.skip_js()?;
+                }
+                "end" if behaviors > 0 => {
+                    self.pos += 1;
+                    behaviors -= 1;
+                }
+                "end" => return Err(self.error_here("unexpected `end`")),
+                feature if UNCHECKED_FEATURES.contains(&feature) => return Ok(()),
+                _ => {
+                    return Err(Error::new(
+                        token.start,
+                        format!(
+                            "expected a feature such as `on`, `init`, or `def`, found {}",
+                            self.found()
+                        ),
+                    ))
+                }
+            }
+        }
+    }
+
+    /// A possibly dotted name, such as `utils.greet`.
+    fn parse_name(&mut self, what: &str) -> Result<(), Error> {
+        if self.peek_ident().is_none() {
+            return Err(self.error_here(format!("expected a {what}, found {}", self.found())));
+        }
+        self.pos += 1;
+        while self.peek().map(|t| t.kind) == Some(Kind::Punct('.')) {
+            self.pos += 1;
+            if self.bump().map(|t| t.kind) != Some(Kind::Ident) {
+                self.pos -= 1;
+                return Err(self.error_here(format!("expected a {what}, found {}", self.found())));
+            }
+        }
+        Ok(())
+    }
+
+    /// An event name such as `click`, `htmx:afterSwap`, or `my-event`.
+    fn parse_event_name(&mut self) -> Result<(), Error> {
+        self.eat_ident("every");
+        let Some(first) = self
+            .peek()
+            .filter(|t| matches!(t.kind, Kind::Ident | Kind::Str))
+        else {
+            return Err(self.error_here(format!("expected an event name, found {}", self.found())));
+        };
+        self.pos += 1;
+        let mut end = first.end;
+        while let [sep, part, ..] = &self.tokens[self.pos..] {
+            let joined = matches!(sep.kind, Kind::Punct(':' | '-' | '.'))
+                && matches!(part.kind, Kind::Ident | Kind::Number)
+                && sep.start == end
+                && part.start == sep.end;
+            if !joined {
+                break;
+            }
+            end = part.end;
+            self.pos += 2;
+        }
+        self.skip_group('[');
+        self.skip_group('(');
+        Ok(())
+    }
+
+    fn parse_on(&mut self) -> Result<(), Error> {
+        self.parse_event_name()?;
+        loop {
+            match self.peek_ident().unwrap_or_default() {
+                "or" => {
+                    self.pos += 1;
+                    self.parse_event_name()?;
+                }
+                "from" | "in" | "of" => {
+                    self.pos += 1;
+                    self.skip_expression();
+                }
+                "elsewhere" | "outside" => self.pos += 1,
+                "having" => {
+                    // such as `having threshold 0.5` for intersection events
+                    self.pos += 1;
+                    self.skip_expression();
+                }
+                "debounced" | "throttled" => {
+                    self.pos += 1;
+                    if !self.eat_ident("at") {
+                        return Err(
+                            self.error_here(format!("expected `at`, found {}", self.found()))
+                        );
+                    }
+                    if self.bump().map(|t| t.kind) != Some(Kind::Number) {
+                        self.pos -= 1;
+                        return Err(
+                            self.error_here(format!("expected a time, found {}", self.found()))
+                        );
+                    }
+                    self.skip_expression();
+                }
+                "queue" => {
+                    self.pos += 1;
+                    match self.peek_ident() {
+                        Some("all" | "first" | "last" | "none") => self.pos += 1,
+                        _ => {
+                            return Err(self.error_here(format!(
+                                "expected `all`, `first`, `last`, or `none`, found {}",
+                                self.found()
+                            )))
+                        }
+                    }
+                }
+                // a count filter, such as `1`, `2 to 10` or `11 and on`
+                _ if self.peek().is_some_and(|t| t.kind == Kind::Number) => {
+                    self.pos += 1;
+                    if self.eat_ident("to") {
+                        if self.bump().map(|t| t.kind) != Some(Kind::Number) {
+                            self.pos -= 1;
+                            return Err(self
+                                .error_here(format!("expected a count, found {}", self.found())));
+                        }
+                    } else if self.eat_ident("and") && !self.eat_ident("on") {
+                        return Err(
+                            self.error_here(format!("expected `on`, found {}", self.found()))
+                        );
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Skips an inline `js ... end` block.
+    fn skip_js(&mut self) -> Result<(), Error> {
+        let start = self.tokens[self.pos - 1].start;
+        while let Some(token) = self.bump() {
+            if token.kind == Kind::Ident && token.text == "end" {
+                return Ok(());
+            }
+        }
+        Err(Error::new(start, "`js` must be closed with `end`"))
+    }
+
+    /// Parses commands until the end of the feature they are in.
+    fn parse_commands(&mut self) -> Result<(), Error> {
+        let mut blocks: Vec<&str> = Vec::new();
+        loop {
+            while self.peek().is_some_and(|t| t.kind == Kind::Newline)
+                || self.peek_ident() == Some("then")
+            {
+                self.pos += 1;
+            }
+            let Some(token) = self.peek() else {
+                return Ok(());
+            };
+            if token.kind != Kind::Ident {
+                return Err(self.error_here(format!("expected a command, found {}", self.found())));
+            }
+            match token.text {
+                "end" => {
+                    self.pos += 1;
+                    if blocks.pop().is_none() {
+                        return Ok(());
+                    }
+                }
+                "else" | "otherwise" => {
+                    if blocks.last() != Some(&"if") {
+                        return Err(self.error_here(format!("`{}` without `if`", token.text)));
+                    }
+                    self.pos += 1;
+                    if self.eat_ident("if") {
+                        self.skip_expression();
+                    }
+                }
+                "catch" | "finally" => {
+                    self.pos += 1;
+                    if token.text == "catch" {
+                        self.parse_name("error name")?;
+                    }
+                }
+                feature if FEATURES.contains(&feature) && self.at_line_start() => return Ok(()),
+                "on" => return Ok(()),
+                "js" => {
+                    self.pos += 1;
+                    self.skip_js()?;
+                }
+                command if COMMANDS.contains(&command) => {
+                    self.pos += 1;
+                    if BLOCKS.contains(&command) {
+                        blocks.push(command);
+                    }
+                    self.skip_expression();
+                }
+                _ => {
+                    let next = self.tokens.get(self.pos + 1).map(|t| t.kind);
+                    if !matches!(next, Some(Kind::Punct('(' | '.'))) {
+                        return Err(self.error_here(format!("unknown command {}", self.found())));
+                    }
+                    // a pseudo-command, such as `reload() the location of the window`
+                    self.pos += 1;
+                    self.skip_expression();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(source: &str) -> String {
+        check(source).unwrap_err().message
+    }
+
+    #[test]
+    fn accepts_valid_scripts() {
+        for source in [
+            "on change log 'hi'",
+            "on click click() the previous <input/>",
+            "on click toggle .active on #menu",
+            "on htmx:afterSwap from body add .fade-in to me then wait 1s then remove .fade-in",
+            "on keyup[key is 'Enter'] debounced at 200ms send search to #results",
+            "on click or touchstart\n  if I match .open\n    remove .open\n  else\n    add .open\n  end\nend",
+            "init set :count to 0\non click increment :count then put :count into me",
+            "def greet(name) log `Hello ${name}` end\non click call greet('you')",
+            "behavior Removable on click remove me end end\ninstall Removable",
+            "on click take .selected from .tab for me",
+            "on click repeat for item in <li/> add .seen to item end",
+            "on click js alert('hi') end",
+            "on my-event(detail) log detail -- a comment",
+            "on click halt the event's default then go to url /home",
+            "on click log (the target of the event)'s id then log my.values[0]'s length",
+            "on click 1 log 'first'",
+            "on click 2 to 10 log 'again'",
+            "on click 11 and on log 'more'",
+            "on intersection(intersecting) having threshold 0.5 if intersecting add .seen end",
+            "on click log x+'s'",
+        ] {
+            assert_eq!(check(source), Ok(()), "{source}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_scripts() {
+        assert_eq!(error("on click toggel .active"), "unknown command `toggel`");
+        assert_eq!(
+            error("onclick log 1"),
+            "expected a feature such as `on`, `init`, or `def`, found `onclick`"
+        );
+        assert_eq!(error("on click log 'hi"), "unterminated string");
+        assert_eq!(
+            error("on click remove <li"),
+            "unterminated query literal, expected it to end with `/>`"
+        );
+        assert_eq!(error("on click call f(1]"), "unexpected `]`");
+        assert_eq!(error("on click call f(1"), "unclosed `(`");
+        assert_eq!(error("on click log 1 else log 2"), "`else` without `if`");
+        assert_eq!(error("on click log 1 end end"), "unexpected `end`");
+        assert_eq!(
+            error("on"),
+            "expected an event name, found the end of the script"
+        );
+        assert_eq!(
+            error("on click debounced 200ms log 1"),
+            "expected `at`, found `200`"
+        );
+        assert_eq!(
+            error("on click 2 to log 1"),
+            "expected a count, found `log`"
+        );
+        assert_eq!(error("on click 2 and log 1"), "expected `on`, found `log`");
+    }
+
+    #[test]
+    fn reports_line_and_column() {
+        let source = "on click\n  add .a\n  tggle .b";
+        let error = check(source).unwrap_err();
+        assert_eq!(error.line_column(source), (3, 3));
+    }
+}
@@ -16,10 +16,13 @@ use rstml::{
 use syn::{
     punctuated::{Pair, Punctuated},
     spanned::Spanned,
-    ExprPath, LitStr,
+    Data, DataStruct, DeriveInput, ExprPath, Fields, LitStr,
 };
 use uuid::Uuid;
 
+#[cfg(any(test, feature = "hyperscript-check"))]
+mod hyperscript;
+
 struct HyperideGenerator {
     bindings: TokenStream2,
     idents: Vec<Ident>,
@@ -180,6 +183,10 @@ impl HyperideGenerator {
                             )
                         }
                         KeyedAttributeValue::Value(expr) => {
+                            #[cfg(feature = "hyperscript-check")]
+                            if matches!(key, NodeName::Path(path) if path.path.is_ident("_")) {
+                                check_hyperscript(&expr.value);
+                            }
                             let hyperide = &self.hyperide;
                             let value = &expr.value;
                             self.push_as_hypertext(quote_spanned! {expr.span()=>
@@ -237,6 +244,43 @@ impl HyperideGenerator {
     }
 }
 
+fn hyperide_path(tokens: &TokenStream2) -> TokenStream2 {
+    let Ok(hyperide) = crate_name("hyperide") else {
+        abort!(tokens, "hyperide crate must be available")
+    };
+    match hyperide {
+        FoundCrate::Itself => quote! { ::hyperide },
+        FoundCrate::Name(name) => {
+            let ident = Ident::new(&name, Span::call_site());
+            quote! { ::#ident }
+        }
+    }
+}
+
+/// Aborts at a literal `_` attribute if it is not valid hyperscript. Other
+/// expressions are only known at runtime, so are not checked.
+#[cfg(feature = "hyperscript-check")]
+fn check_hyperscript(value: &syn::Expr) {
+    let syn::Expr::Lit(syn::ExprLit {
+        lit: syn::Lit::Str(lit),
+        ..
+    }) = value
+    else {
+        return;
+    };
+    let source = lit.value();
+    if let Err(error) = hyperscript::check(&source) {
+        let (line, column) = error.line_column(&source);
+        abort!(
+            lit,
+            "Invalid hyperscript at line {}, column {}: {}",
+            line,
+            column,
+            error.message
+        );
+    }
+}
+
 fn make_ident(span: Span) -> Ident {
     Ident::new(
         &format!("__hyperide_internal_{}", Uuid::new_v4().simple()),
@@ -270,14 +314,15 @@ fn get_punct_hypertext<T>(punct: &Punctuated<impl Display, T>) -> String {
     name
 }
 
-/// Converts a HTML like syntax into a string.
+/// Converts a HTML like syntax into a `hyperide::Markup`, which derefs to
+/// `str` and converts into a `String`.
 ///
 /// ```rust
-/// use hyperide::hyperide;
+/// use hyperide::{hyperide, Markup};
 /// fn returns_tag() -> char {
 ///     'p'
 /// }
-/// fn my_component(a: &str, b: &str) -> String {
+/// fn my_component(a: &str, b: &str) -> Markup {
 ///     hyperide! {
 ///         <p><strong>{a}{": "}</strong>{b}</p>
 ///     }
@@ -317,16 +362,7 @@ fn get_punct_hypertext<T>(punct: &Punctuated<impl Display, T>) -> String {
 #[proc_macro_error::proc_macro_error]
 #[proc_macro]
 pub fn hyperide(tokens: TokenStream) -> TokenStream {
-    let Ok(hyperide) = crate_name("hyperide") else {
-        abort!(proc_macro2::TokenStream::from(tokens), "hyperide crate must be available")
-    };
-    let hyperide = match hyperide {
-        FoundCrate::Itself => quote! { ::hyperide },
-        FoundCrate::Name(name) => {
-            let ident = Ident::new(&name, Span::call_site());
-            quote! { ::#ident }
-        }
-    };
+    let hyperide = hyperide_path(&tokens.clone().into());
 
     let config = ParserConfig::new()
         .recover_block(true)
@@ -345,7 +381,7 @@ pub fn hyperide(tokens: TokenStream) -> TokenStream {
     let parser = Parser::new(config);
     let (nodes, errors) = parser.parse_recoverable(tokens).split_vec();
 
-    let mut walker = HyperideGenerator::new(hyperide);
+    let mut walker = HyperideGenerator::new(hyperide.clone());
     walker.push_nodes(&nodes);
 
     let idents = walker.idents;
@@ -364,8 +400,128 @@ pub fn hyperide(tokens: TokenStream) -> TokenStream {
         #(
             #string_out.push_str(std::ops::Deref::deref(&#idents));
         )*
-        #string_out
+        #hyperide::Markup::from(#string_out)
     }};
 
     out.into()
 }
+
+/// Derives `hyperide::route::TypedRoute` for a struct describing the path of
+/// an axum route. Every `:param` and `*wildcard` in the path given by the
+/// `#[route]` attribute must be a field of the struct, and every field must be
+/// in the path, so changing a route without updating its uses fails to
+/// compile.
+///
+/// ```rust
+/// use hyperide::{hyperide, route::TypedRoute};
+/// #[derive(TypedRoute)]
+/// #[route("/hello/:name")]
+/// struct Hello {
+///     name: String,
+/// }
+/// let link = hyperide! {
+///     <a href={Hello { name: "Ferris Crab".into() }}>"Say hello"</a>
+/// };
+/// assert_eq!(link, r#"<a href="/hello/Ferris%20Crab">Say hello</a>"#);
+/// ```
+#[proc_macro_error::proc_macro_error]
+#[proc_macro_derive(TypedRoute, attributes(route))]
+pub fn derive_typed_route(tokens: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(tokens as DeriveInput);
+    let hyperide = hyperide_path(&input.ident.to_token_stream());
+
+    let Some(route) = input
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("route"))
+    else {
+        abort!(input.ident, "Expected #[route(\"/path\")] attribute")
+    };
+    let path = match route.parse_args::<LitStr>() {
+        Ok(path) => path,
+        Err(e) => abort!(e.span(), "Expected #[route(\"/path\")] attribute"),
+    };
+    let path_str = path.value();
+    if !path_str.starts_with('/') {
+        abort!(path, "Route must start with `/`");
+    }
+
+    let fields = match &input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(named),
+            ..
+        }) => named.named.iter().filter_map(|f| f.ident.clone()).collect(),
+        Data::Struct(DataStruct {
+            fields: Fields::Unit,
+            ..
+        }) => Vec::new(),
+        _ => abort!(
+            input.ident,
+            "TypedRoute can only be derived for structs with named fields"
+        ),
+    };
+
+    let mut used = Vec::new();
+    let mut pushes = TokenStream2::new();
+    for segment in path_str.split('/').skip(1) {
+        pushes.extend(quote! { url.push('/'); });
+        let (param, encode) = if let Some(param) = segment.strip_prefix(':') {
+            (param, quote! { encode_segment })
+        } else if let Some(param) = segment.strip_prefix('*') {
+            (param, quote! { encode_wildcard })
+        } else {
+            pushes.extend(quote! { url.push_str(#segment); });
+            continue;
+        };
+        let Some(field) = fields.iter().find(|field| *field == param) else {
+            abort!(path, "Route parameter `{}` is not a field", param)
+        };
+        used.push(field);
+        pushes.extend(quote! {
+            url.push_str(&#hyperide::route::#encode(&self.#field));
+        });
+    }
+    if let Some(unused) = fields.iter().find(|field| !used.contains(field)) {
+        abort!(unused, "Field `{}` is not a parameter of the route", unused);
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let mut attr_generics = input.generics.clone();
+    attr_generics
+        .params
+        .insert(0, syn::parse_quote! { '__hyperide });
+    let (attr_impl_generics, _, _) = attr_generics.split_for_impl();
+    quote! {
+        impl #impl_generics #hyperide::route::TypedRoute for #name #ty_generics #where_clause {
+            const PATH: &'static str = #path;
+
+            fn to_url(&self) -> String {
+                let mut url = String::new();
+                #pushes
+                url
+            }
+        }
+        impl #attr_impl_generics #hyperide::IntoAttrText<'__hyperide> for #name #ty_generics
+        #where_clause
+        {
+            fn into_attr_text(
+                self,
+                attr: impl #hyperide::IntoHyperText<'__hyperide>,
+            ) -> #hyperide::HyperText<'__hyperide> {
+                #hyperide::route::url_attr_text(attr, &self)
+            }
+        }
+        impl #attr_impl_generics #hyperide::IntoAttrText<'__hyperide> for &#name #ty_generics
+        #where_clause
+        {
+            fn into_attr_text(
+                self,
+                attr: impl #hyperide::IntoHyperText<'__hyperide>,
+            ) -> #hyperide::HyperText<'__hyperide> {
+                #hyperide::route::url_attr_text(attr, self)
+            }
+        }
+    }
+    .into()
+}
@@ -0,0 +1,6 @@
+use hyperide::route::TypedRoute;
+
+#[derive(TypedRoute)]
+struct Home;
+
+fn main() {}
@@ -0,0 +1,9 @@
+use hyperide::route::TypedRoute;
+
+#[derive(TypedRoute)]
+#[route("users/:id")]
+struct User {
+    id: u32,
+}
+
+fn main() {}
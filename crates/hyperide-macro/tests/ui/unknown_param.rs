@@ -0,0 +1,9 @@
+use hyperide::route::TypedRoute;
+
+#[derive(TypedRoute)]
+#[route("/users/:id/posts/:post")]
+struct Post {
+    id: u32,
+}
+
+fn main() {}
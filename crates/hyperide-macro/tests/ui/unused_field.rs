@@ -0,0 +1,10 @@
+use hyperide::route::TypedRoute;
+
+#[derive(TypedRoute)]
+#[route("/users/:id")]
+struct User {
+    id: u32,
+    tab: String,
+}
+
+fn main() {}
@@ -1,8 +1,10 @@
-use std::path::Path;
+use hyperide::tailwind::Tailwind;
 
 fn main() {
-    hyperide::tailwind::bootstrap(
-        Path::new("./tailwind.config.js"),
-        Path::new("./src/tailwind.css"),
-    );
+    // Builds with an empty stylesheet when tailwind is not installed
+    let _ = Tailwind::new()
+        .config("tailwind.config.js")
+        .input("src/tailwind.css")
+        .content_globs(["src/**/*.rs"])
+        .run();
 }
@@ -1,12 +1,13 @@
-use axum::{response::Html, routing::get, Router};
-use hyperide::{
-    htmx::include_htmx, hyperide, hyperscript::include_hyperscript, tailwind::include_tailwind,
-};
+use axum::{response::IntoResponse, routing::get, Router};
+use hyperide::{htmx, hyperide, hyperscript, layout::Layout, tailwind::include_tailwind, Markup};
 use std::net::SocketAddr;
 
 #[tokio::main]
 async fn main() {
-    let app = Router::new().route("/", get(todos));
+    let app = Router::new()
+        .route("/", get(todos))
+        .merge(hyperide::assets::routes())
+        .merge(hyperide::tailwind::dev::routes(env!("OUT_DIR")));
 
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
     axum::Server::bind(&addr)
@@ -15,21 +16,21 @@ async fn main() {
         .unwrap();
 }
 
-fn base_page(content: String) -> Html<String> {
-    Html(hyperide! {
+fn base_page(content: Markup) -> Markup {
+    hyperide! {
         <!DOCTYPE html>
         <html lang="en">
         <head>
             <title>"Todo App"</title>
             { include_tailwind!() }
-            { include_htmx!() }
-            { include_hyperscript!() }
+            { htmx::script_tag!() }
+            { hyperscript::script_tag!() }
         </head>
         <body class="min-h-screen bg-gray-200">
             { content }
         </body>
         </html>
-    })
+    }
 }
 
 struct Todo {
@@ -37,7 +38,7 @@ struct Todo {
     completed: bool,
 }
 impl Todo {
-    fn get(&self, _id: usize) -> String {
+    fn get(&self, _id: usize) -> Markup {
         hyperide! {
             <form class="flex gap-4 bg-gray-50 p-2 rounded">
                 <input type="checkbox" checked={self.completed} _="on change log 'hi'"/>
@@ -53,7 +54,7 @@ impl Todo {
         }
     }
 
-    fn edit(&self, _id: usize) -> String {
+    fn edit(&self, _id: usize) -> Markup {
         hyperide! {
             <form class="flex gap-4 bg-gray-50 p-2 rounded">
                 <input type="checkbox" />
@@ -65,7 +66,7 @@ impl Todo {
     }
 }
 
-async fn todos() -> Html<String> {
+async fn todos(layout: Layout) -> impl IntoResponse {
     let todos: &[Todo] = &[
         Todo {
             value: "Make program work".into(),
@@ -76,7 +77,7 @@ async fn todos() -> Html<String> {
             completed: true,
         },
     ];
-    base_page(hyperide! {
+    let content = hyperide! {
         <div class="my-4 p-4 max-w-screen-sm mx-auto">
             <h1 class="text-xl font-bold mb-4">Todo App</h1>
             <ul class="flex flex-col gap-2">{
@@ -84,8 +85,9 @@ async fn todos() -> Html<String> {
                     .iter()
                     .enumerate()
                     .map(|(id, todo)| todo.get(id))
-                    .collect::<String>()
+                    .collect::<Markup>()
             }</ul>
         </div>
-    })
+    };
+    layout.render(content, base_page)
 }
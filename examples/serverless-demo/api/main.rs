@@ -1,24 +1,40 @@
-use axum::{extract::Path, response::Html, routing::get, Router};
-use hyperide::{hyperide, tailwind::include_tailwind};
+use axum::{extract::Path, routing::get, Router};
+use hyperide::{
+    hyperide,
+    route::{RouterExt, TypedRoute},
+    tailwind::include_tailwind,
+    Markup,
+};
+use serde::Deserialize;
 use vercel_runtime::Error;
 
+#[derive(TypedRoute, Deserialize)]
+#[route("/hello/:name")]
+struct Hello {
+    name: String,
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Error> {
     let app = Router::new()
         .route("/", get(root))
-        .route("/hello/:name", get(greet));
-    hyperide::vercel::run(app).await
+        .typed_route::<Hello>(get(greet));
+    if std::env::var_os("VERCEL").is_some() {
+        hyperide::vercel::run(app).await
+    } else {
+        hyperide::vercel::serve_local(app, ([127, 0, 0, 1], 3000).into()).await
+    }
 }
 
-async fn root() -> Html<String> {
-    Html(greeting("world"))
+async fn root() -> Markup {
+    greeting("world")
 }
 
-async fn greet(Path((name,)): Path<(String,)>) -> Html<String> {
-    Html(greeting(&name))
+async fn greet(Path(Hello { name }): Path<Hello>) -> Markup {
+    greeting(&name)
 }
 
-fn greeting(name: &str) -> String {
+fn greeting(name: &str) -> Markup {
     hyperide! {
         <!DOCTYPE html>
         <html lang="en">
@@ -27,6 +43,7 @@ fn greeting(name: &str) -> String {
         </head>
         <body>
             <p class="text-xl m-5">{"Hello, "}<strong>{name}</strong>{"!"}</p>
+            <a class="m-5 underline" href={Hello { name: "hyperide".into() }}>"Greet hyperide"</a>
             <div data-foo="bar"></div>
         </body>
         </html>
@@ -1,8 +1,10 @@
-use std::path::Path;
+use hyperide::tailwind::Tailwind;
 
 fn main() {
-    hyperide::tailwind::bootstrap(
-        Path::new("./tailwind.config.js"),
-        Path::new("./tailwind.in.css"),
-    );
+    // Builds with an empty stylesheet when tailwind is not installed
+    let _ = Tailwind::new()
+        .config("tailwind.config.js")
+        .input("tailwind.in.css")
+        .content_globs(["src/**/*.rs", "api/**/*.rs"])
+        .run();
 }
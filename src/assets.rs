@@ -0,0 +1,123 @@
+use std::sync::OnceLock;
+
+use axum::{
+    http::header::{CACHE_CONTROL, CONTENT_TYPE},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+
+use crate::hash::fnv1a;
+
+const JAVASCRIPT: &str = "text/javascript; charset=utf-8";
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// Routes serving htmx and hyperscript under urls containing a hash of their
+/// content, so browsers can cache them forever. Merge these into your router,
+/// and use [`crate::htmx::script_tag!`] and [`crate::hyperscript::script_tag!`]
+/// to reference them.
+///
+/// ```no_run
+/// # use axum::Router;
+/// let app: Router = Router::new().merge(hyperide::assets::routes());
+/// ```
+pub fn routes<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let router = Router::new();
+    #[cfg(feature = "htmx")]
+    let router = router.route(htmx_path(), get(|| serve(crate::htmx::SCRIPT)));
+    #[cfg(feature = "hyperscript")]
+    let router = router.route(
+        hyperscript_path(),
+        get(|| serve(crate::hyperscript::SCRIPT)),
+    );
+    router
+}
+
+async fn serve(script: &'static str) -> impl IntoResponse {
+    (
+        [(CONTENT_TYPE, JAVASCRIPT), (CACHE_CONTROL, IMMUTABLE)],
+        script,
+    )
+}
+
+/// The path [`routes`] serves htmx at.
+#[cfg(feature = "htmx")]
+pub fn htmx_path() -> &'static str {
+    static PATH: OnceLock<String> = OnceLock::new();
+    PATH.get_or_init(|| hashed_path("htmx", crate::htmx::SCRIPT))
+}
+
+/// The path [`routes`] serves hyperscript at.
+#[cfg(feature = "hyperscript")]
+pub fn hyperscript_path() -> &'static str {
+    static PATH: OnceLock<String> = OnceLock::new();
+    PATH.get_or_init(|| hashed_path("hyperscript", crate::hyperscript::SCRIPT))
+}
+
+fn hashed_path(name: &str, content: &str) -> String {
+    format!(
+        "/_hyperide/{name}.{:016x}.min.js",
+        fnv1a(content.as_bytes())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::{Body, HttpBody},
+        http::{Request, StatusCode},
+    };
+    use tower_service::Service;
+
+    use super::*;
+
+    async fn get_path(path: &str) -> axum::response::Response {
+        let mut app: Router = routes();
+        let request = Request::get(path).body(Body::empty()).unwrap();
+        app.call(request).await.unwrap()
+    }
+
+    async fn serves(path: &str, script: &str) {
+        let mut response = get_path(path).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], JAVASCRIPT);
+        assert_eq!(response.headers()[CACHE_CONTROL], IMMUTABLE);
+        let mut body = Vec::new();
+        while let Some(chunk) = response.body_mut().data().await {
+            body.extend(chunk.unwrap());
+        }
+        assert_eq!(body, script.as_bytes());
+    }
+
+    #[test]
+    fn hashes_content_into_paths() {
+        let path = hashed_path("htmx", "a");
+        assert_eq!(path, "/_hyperide/htmx.af63dc4c8601ec8c.min.js");
+        assert_ne!(path, hashed_path("htmx", "b"));
+    }
+
+    #[cfg(feature = "htmx")]
+    #[tokio::test]
+    async fn serves_htmx() {
+        assert!(htmx_path().starts_with("/_hyperide/htmx."));
+        serves(htmx_path(), crate::htmx::SCRIPT).await;
+    }
+
+    #[cfg(feature = "hyperscript")]
+    #[tokio::test]
+    async fn serves_hyperscript() {
+        assert!(hyperscript_path().starts_with("/_hyperide/hyperscript."));
+        serves(hyperscript_path(), crate::hyperscript::SCRIPT).await;
+    }
+
+    #[tokio::test]
+    async fn stale_hashes_are_not_found() {
+        for name in ["htmx", "hyperscript"] {
+            let stale = hashed_path(name, "an older release");
+            assert_eq!(get_path(&stale).await.status(), StatusCode::NOT_FOUND);
+        }
+    }
+}
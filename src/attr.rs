@@ -4,6 +4,15 @@ pub trait IntoAttrText<'a> {
     fn into_attr_text(self, attr: impl IntoHyperText<'a>) -> HyperText<'a>;
 }
 
+/// Formats `attr="value"`, escaping `value` so that it cannot end the
+/// attribute early.
+#[cfg(any(feature = "axum", feature = "htmx", feature = "hyperscript"))]
+pub(crate) fn escaped_attr_text<'a>(attr: impl IntoHyperText<'a>, value: &str) -> HyperText<'a> {
+    let attr: &str = &attr.into_hyper_text();
+    let value = html_escape::encode_double_quoted_attribute(value);
+    format!("{attr}=\"{value}\"").into()
+}
+
 macro_rules! impl_to_attr {
     ($t:ty) => {
         impl<'a> IntoAttrText<'a> for $t {
@@ -0,0 +1,335 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use axum::http::{
+    header::{
+        ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
+        ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS,
+        ACCESS_CONTROL_REQUEST_METHOD, CONTENT_TYPE, ORIGIN, VARY,
+    },
+    HeaderMap, HeaderValue, Method, Request, Response, StatusCode,
+};
+use htmx_headers::{headers_for, Direction, HtmxVersion};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// The methods htmx can issue requests with.
+const HTMX_METHODS: &str = "GET, POST, PUT, PATCH, DELETE";
+
+/// A tower [`Layer`] that lets htmx on other origins talk to this server.
+///
+/// Browsers drop `HX-*` response headers from cross origin responses unless
+/// they are listed in `Access-Control-Expose-Headers`, and refuse to send
+/// `HX-*` request headers unless a preflight allows them through
+/// `Access-Control-Allow-Headers`. Both lists are generated from
+/// [`htmx_headers::HEADERS`].
+///
+/// ```no_run
+/// # use axum::Router;
+/// use hyperide::cors::HtmxCorsLayer;
+/// let app: Router = Router::new()
+///     .layer(HtmxCorsLayer::new().allow_origin("https://example.com"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct HtmxCorsLayer {
+    origins: AllowedOrigins,
+    version: HtmxVersion,
+    credentials: bool,
+}
+impl HtmxCorsLayer {
+    /// A layer that allows no origins, and knows the headers of the latest
+    /// version of htmx.
+    pub fn new() -> HtmxCorsLayer {
+        HtmxCorsLayer {
+            origins: AllowedOrigins::List(Vec::new()),
+            version: HtmxVersion::new(u16::MAX, u16::MAX, u16::MAX),
+            credentials: false,
+        }
+    }
+
+    /// Allows requests from `origin`, such as `https://example.com`.
+    ///
+    /// # Panics
+    ///
+    /// If `origin` is not a valid header value.
+    pub fn allow_origin(mut self, origin: &str) -> HtmxCorsLayer {
+        let origin = HeaderValue::from_str(origin).expect("Origin Is Not A Valid Header Value");
+        match &mut self.origins {
+            AllowedOrigins::Any => {}
+            AllowedOrigins::List(origins) => origins.push(origin),
+        }
+        self
+    }
+
+    /// Allows requests from each of `origins`. See [`Self::allow_origin`].
+    pub fn allow_origins<'o>(self, origins: impl IntoIterator<Item = &'o str>) -> HtmxCorsLayer {
+        origins.into_iter().fold(self, Self::allow_origin)
+    }
+
+    /// Allows requests from any origin.
+    pub fn allow_any_origin(mut self) -> HtmxCorsLayer {
+        self.origins = AllowedOrigins::Any;
+        self
+    }
+
+    /// Only allow and expose the headers known to `version` of htmx.
+    pub fn htmx_version(mut self, version: HtmxVersion) -> HtmxCorsLayer {
+        self.version = version;
+        self
+    }
+
+    /// Sets `Access-Control-Allow-Credentials`, needed when htmx is configured
+    /// with `withCredentials`.
+    pub fn allow_credentials(mut self, credentials: bool) -> HtmxCorsLayer {
+        self.credentials = credentials;
+        self
+    }
+}
+impl Default for HtmxCorsLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<S> Layer<S> for HtmxCorsLayer {
+    type Service = HtmxCors<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        let allow_headers = headers_for(Direction::Request, self.version)
+            .flat_map(|header| [Some(header.name), header.auto_encoded])
+            .flatten()
+            .chain([CONTENT_TYPE.as_str()]);
+        let expose_headers =
+            headers_for(Direction::Response, self.version).map(|header| header.name);
+        HtmxCors {
+            inner,
+            config: Arc::new(CorsConfig {
+                origins: self.origins.clone(),
+                credentials: self.credentials,
+                allow_headers: join_header_value(allow_headers),
+                expose_headers: join_header_value(expose_headers),
+            }),
+        }
+    }
+}
+
+/// The [`Service`] produced by [`HtmxCorsLayer`].
+#[derive(Debug, Clone)]
+pub struct HtmxCors<S> {
+    inner: S,
+    config: Arc<CorsConfig>,
+}
+
+#[derive(Debug, Clone)]
+enum AllowedOrigins {
+    Any,
+    List(Vec<HeaderValue>),
+}
+
+#[derive(Debug)]
+struct CorsConfig {
+    origins: AllowedOrigins,
+    credentials: bool,
+    allow_headers: HeaderValue,
+    expose_headers: HeaderValue,
+}
+impl CorsConfig {
+    /// The `Access-Control-Allow-Origin` to respond to a request with, if its
+    /// origin is allowed.
+    fn allowed_origin(&self, headers: &HeaderMap) -> Option<HeaderValue> {
+        let origin = headers.get(ORIGIN)?;
+        match &self.origins {
+            AllowedOrigins::Any if !self.credentials => Some(HeaderValue::from_static("*")),
+            AllowedOrigins::Any => Some(origin.clone()),
+            AllowedOrigins::List(origins) => origins.contains(origin).then(|| origin.clone()),
+        }
+    }
+
+    /// Whether the response depends on the request's origin, in which case
+    /// caches must store it per origin, including for origins which are not
+    /// allowed.
+    fn varies(&self) -> bool {
+        match self.origins {
+            AllowedOrigins::Any => self.credentials,
+            AllowedOrigins::List(_) => true,
+        }
+    }
+
+    fn apply(&self, headers: &mut HeaderMap, origin: Option<HeaderValue>) {
+        if self.varies() {
+            headers.append(VARY, HeaderValue::from_static("origin"));
+        }
+        let Some(origin) = origin else {
+            return;
+        };
+        headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        if self.credentials {
+            headers.insert(
+                ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+    }
+}
+
+fn join_header_value<'h>(names: impl Iterator<Item = &'h str>) -> HeaderValue {
+    let joined = names.collect::<Vec<_>>().join(", ");
+    HeaderValue::from_str(&joined).expect("Header Names Are Valid Header Values")
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for HtmxCors<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    ResBody: Default + Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let config = self.config.clone();
+        let origin = config.allowed_origin(req.headers());
+
+        let is_preflight = req.method() == Method::OPTIONS
+            && req.headers().contains_key(ACCESS_CONTROL_REQUEST_METHOD);
+        if is_preflight && origin.is_some() {
+            let mut response = Response::new(ResBody::default());
+            *response.status_mut() = StatusCode::NO_CONTENT;
+            let headers = response.headers_mut();
+            config.apply(headers, origin);
+            headers.insert(
+                ACCESS_CONTROL_ALLOW_METHODS,
+                HeaderValue::from_static(HTMX_METHODS),
+            );
+            headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, config.allow_headers.clone());
+            return Box::pin(async move { Ok(response) });
+        }
+
+        let future = self.inner.call(req);
+        Box::pin(async move {
+            let mut response = future.await?;
+            let headers = response.headers_mut();
+            if origin.is_some() {
+                headers.insert(ACCESS_CONTROL_EXPOSE_HEADERS, config.expose_headers.clone());
+            }
+            config.apply(headers, origin);
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, routing::get, Router};
+
+    use super::*;
+
+    async fn send(layer: HtmxCorsLayer, request: Request<Body>) -> Response<axum::body::BoxBody> {
+        let mut app = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(layer);
+        app.call(request).await.unwrap()
+    }
+
+    fn preflight(origin: &str) -> Request<Body> {
+        Request::options("/")
+            .header(ORIGIN, origin)
+            .header(ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    fn get_from(origin: &str) -> Request<Body> {
+        Request::get("/")
+            .header(ORIGIN, origin)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    fn layer() -> HtmxCorsLayer {
+        HtmxCorsLayer::new().allow_origin("https://allowed.example")
+    }
+
+    #[tokio::test]
+    async fn allows_preflights_from_allowed_origins() {
+        let response = send(layer(), preflight("https://allowed.example")).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let headers = response.headers();
+        assert_eq!(
+            headers[ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://allowed.example"
+        );
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_METHODS], HTMX_METHODS);
+        let allowed = headers[ACCESS_CONTROL_ALLOW_HEADERS].to_str().unwrap();
+        assert!(allowed.split(", ").any(|name| name == "hx-request"));
+        assert!(allowed.split(", ").any(|name| name == "content-type"));
+        assert!(allowed
+            .split(", ")
+            .any(|name| name == "hx-prompt-uri-autoencoded"));
+        assert_eq!(headers[VARY], "origin");
+    }
+
+    #[tokio::test]
+    async fn passes_through_preflights_from_other_origins() {
+        let response = send(layer(), preflight("https://other.example")).await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        let headers = response.headers();
+        assert!(headers.get(ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+        assert!(headers.get(ACCESS_CONTROL_ALLOW_HEADERS).is_none());
+        assert_eq!(headers[VARY], "origin");
+    }
+
+    #[tokio::test]
+    async fn varies_on_origin_without_one() {
+        let request = Request::get("/").body(Body::empty()).unwrap();
+        let response = send(layer(), request).await;
+        assert!(response
+            .headers()
+            .get(ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none());
+        assert_eq!(response.headers()[VARY], "origin");
+    }
+
+    #[tokio::test]
+    async fn exposes_headers_to_allowed_origins() {
+        let response = send(layer(), get_from("https://allowed.example")).await;
+        let headers = response.headers();
+        assert_eq!(
+            headers[ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://allowed.example"
+        );
+        let exposed = headers[ACCESS_CONTROL_EXPOSE_HEADERS].to_str().unwrap();
+        assert!(exposed.split(", ").any(|name| name == "hx-trigger"));
+        assert!(!exposed.split(", ").any(|name| name == "hx-request"));
+    }
+
+    #[tokio::test]
+    async fn allows_any_origin() {
+        let layer = HtmxCorsLayer::new().allow_any_origin();
+        let response = send(layer, get_from("https://any.example")).await;
+        let headers = response.headers();
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        assert!(headers.get(ACCESS_CONTROL_ALLOW_CREDENTIALS).is_none());
+        assert!(headers.get(VARY).is_none());
+    }
+
+    #[tokio::test]
+    async fn echoes_any_origin_with_credentials() {
+        let layer = HtmxCorsLayer::new()
+            .allow_any_origin()
+            .allow_credentials(true);
+        let response = send(layer, get_from("https://any.example")).await;
+        let headers = response.headers();
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_ORIGIN], "https://any.example");
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert_eq!(headers[VARY], "origin");
+    }
+}
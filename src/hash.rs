@@ -0,0 +1,7 @@
+/// A stable hash, unlike [`std::hash::DefaultHasher`], so urls and etags do
+/// not change between builds of the same content.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
@@ -14,6 +14,84 @@ macro_rules! include_htmx {
 
 pub use include_htmx;
 
+/// A `<script>` tag loading htmx from [`crate::assets::routes`], which
+/// browsers can cache instead of downloading it inline with every page.
+#[cfg(feature = "axum")]
+#[macro_export]
+macro_rules! htmx_script_tag {
+    () => {
+        $crate::hyperide! {
+            <script src={$crate::assets::htmx_path()}></script>
+        }
+    };
+}
+
+#[cfg(feature = "axum")]
+pub use htmx_script_tag as script_tag;
+
+/// The bundled [htmx extensions](https://htmx.org/extensions/), each only
+/// included when its `htmx-ext-*` feature is enabled.
+#[doc(hidden)]
+pub mod ext {
+    #[cfg(feature = "htmx-ext-class-tools")]
+    pub const CLASS_TOOLS: &str = include_str!("htmx-ext/class-tools.js");
+    #[cfg(feature = "htmx-ext-head-support")]
+    pub const HEAD_SUPPORT: &str = include_str!("htmx-ext/head-support.js");
+    #[cfg(feature = "htmx-ext-json-enc")]
+    pub const JSON_ENC: &str = include_str!("htmx-ext/json-enc.js");
+    #[cfg(feature = "htmx-ext-loading-states")]
+    pub const LOADING_STATES: &str = include_str!("htmx-ext/loading-states.js");
+    #[cfg(feature = "htmx-ext-preload")]
+    pub const PRELOAD: &str = include_str!("htmx-ext/preload.js");
+    #[cfg(feature = "htmx-ext-response-targets")]
+    pub const RESPONSE_TARGETS: &str = include_str!("htmx-ext/response-targets.js");
+    #[cfg(feature = "htmx-ext-sse")]
+    pub const SSE: &str = include_str!("htmx-ext/sse.js");
+    #[cfg(feature = "htmx-ext-ws")]
+    pub const WS: &str = include_str!("htmx-ext/ws.js");
+}
+
+/// Includes an official htmx extension inline, which must be after htmx
+/// itself. Each extension needs its cargo feature enabled, for example
+/// `include_htmx_ext!("json-enc")` needs `htmx-ext-json-enc`. Enable it on an
+/// element with [`crate::hx::ext`].
+#[macro_export]
+macro_rules! include_htmx_ext {
+    ("class-tools") => {
+        $crate::include_htmx_ext!(@script $crate::htmx::ext::CLASS_TOOLS)
+    };
+    ("head-support") => {
+        $crate::include_htmx_ext!(@script $crate::htmx::ext::HEAD_SUPPORT)
+    };
+    ("json-enc") => {
+        $crate::include_htmx_ext!(@script $crate::htmx::ext::JSON_ENC)
+    };
+    ("loading-states") => {
+        $crate::include_htmx_ext!(@script $crate::htmx::ext::LOADING_STATES)
+    };
+    ("preload") => {
+        $crate::include_htmx_ext!(@script $crate::htmx::ext::PRELOAD)
+    };
+    ("response-targets") => {
+        $crate::include_htmx_ext!(@script $crate::htmx::ext::RESPONSE_TARGETS)
+    };
+    ("sse") => {
+        $crate::include_htmx_ext!(@script $crate::htmx::ext::SSE)
+    };
+    ("ws") => {
+        $crate::include_htmx_ext!(@script $crate::htmx::ext::WS)
+    };
+    (@script $script:expr) => {
+        $crate::hyperide! {
+            <script _hr_no_raw=true>
+                { $script }
+            </script>
+        }
+    };
+}
+
+pub use include_htmx_ext;
+
 pub mod headers {
     pub use htmx_headers::*;
 }
@@ -0,0 +1,306 @@
+//! Typed values for htmx `hx-*` attributes, so mistakes in htmx syntax are
+//! caught when compiling rather than in the browser console.
+//!
+//! ```rust
+//! use hyperide::{hx, hyperide};
+//! let search = hyperide! {
+//!     <input
+//!         name="q"
+//!         hx-get={hx::get("/search")}
+//!         hx-trigger={hx::trigger().event("keyup").changed().delay(hx::ms(500))}
+//!         hx-target={hx::target("#results")}
+//!         hx-swap={hx::swap(hx::SwapStyle::OuterHtml)}
+//!         hx-vals={hx::vals().string("page", "1")}
+//!     />
+//! };
+//! assert_eq!(
+//!     search,
+//!     concat!(
+//!         r#"<input name="q" hx-get="/search" hx-trigger="keyup changed delay:500ms" "#,
+//!         r##"hx-target="#results" hx-swap="outerHTML" "##,
+//!         r#"hx-vals="{&quot;page&quot;:&quot;1&quot;}">"#,
+//!     ),
+//! );
+//! ```
+
+use std::{fmt, time::Duration};
+
+pub use htmx_headers::swap::{ScrollPosition, Swap, SwapStyle};
+pub use htmx_headers::trigger::{Queue, Trigger, TriggerBuilder};
+
+use htmx_headers::push_json_str;
+
+use crate::{attr::escaped_attr_text, HyperText, IntoAttrText, IntoHyperText};
+
+/// An interval of `ms` milliseconds.
+pub fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// An interval of `secs` seconds.
+pub fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+/// A url to request, for `hx-get`, `hx-post`, `hx-put`, `hx-patch`, and
+/// `hx-delete`.
+///
+/// htmx takes the method from the attribute, not the value, so [`get`],
+/// [`post`], [`put`], [`patch`] and [`delete`] are aliases which only make
+/// templates read better. `hx-post={hx::get("/todos")}` still posts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Url(String);
+impl fmt::Display for Url {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The value of `hx-get`.
+pub fn get(url: impl Into<String>) -> Url {
+    Url(url.into())
+}
+
+/// The value of `hx-post`, an alias of [`get`].
+pub fn post(url: impl Into<String>) -> Url {
+    Url(url.into())
+}
+
+/// The value of `hx-put`, an alias of [`get`].
+pub fn put(url: impl Into<String>) -> Url {
+    Url(url.into())
+}
+
+/// The value of `hx-patch`, an alias of [`get`].
+pub fn patch(url: impl Into<String>) -> Url {
+    Url(url.into())
+}
+
+/// The value of `hx-delete`, an alias of [`get`].
+pub fn delete(url: impl Into<String>) -> Url {
+    Url(url.into())
+}
+
+/// The element to swap content into, for `hx-target`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Target {
+    /// The element with the attribute.
+    This,
+    /// The first element matching a CSS selector.
+    Selector(String),
+    /// The closest ancestor matching a CSS selector.
+    Closest(String),
+    /// The first child matching a CSS selector.
+    Find(String),
+    /// The next sibling, or the next element matching a CSS selector.
+    Next(Option<String>),
+    /// The previous sibling, or the previous element matching a CSS selector.
+    Previous(Option<String>),
+}
+impl From<&str> for Target {
+    fn from(selector: &str) -> Self {
+        Target::Selector(selector.to_string())
+    }
+}
+impl From<String> for Target {
+    fn from(selector: String) -> Self {
+        Target::Selector(selector)
+    }
+}
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::This => f.write_str("this"),
+            Target::Selector(selector) => f.write_str(selector),
+            Target::Closest(selector) => write!(f, "closest {selector}"),
+            Target::Find(selector) => write!(f, "find {selector}"),
+            Target::Next(None) => f.write_str("next"),
+            Target::Next(Some(selector)) => write!(f, "next {selector}"),
+            Target::Previous(None) => f.write_str("previous"),
+            Target::Previous(Some(selector)) => write!(f, "previous {selector}"),
+        }
+    }
+}
+
+/// The value of `hx-target`.
+pub fn target(target: impl Into<Target>) -> Target {
+    target.into()
+}
+
+/// The value of `hx-swap`.
+pub fn swap(swap: impl Into<Swap>) -> Swap {
+    swap.into()
+}
+
+/// The value of `hx-trigger`, which must be given at least one event.
+pub fn trigger() -> TriggerBuilder {
+    Trigger::builder()
+}
+
+/// Extra values to submit with a request, for `hx-vals`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Vals {
+    json: String,
+}
+impl Vals {
+    fn key(mut self, key: &str) -> Vals {
+        self.json.push(if self.json.is_empty() { '{' } else { ',' });
+        push_json_str(&mut self.json, key);
+        self.json.push(':');
+        self
+    }
+
+    /// Submits `key` as the string `value`.
+    pub fn string(self, key: &str, value: &str) -> Vals {
+        let mut vals = self.key(key);
+        push_json_str(&mut vals.json, value);
+        vals
+    }
+
+    /// Submits `key` as a JSON value. `json` is not checked.
+    pub fn raw(self, key: &str, json: &str) -> Vals {
+        let mut vals = self.key(key);
+        vals.json.push_str(json);
+        vals
+    }
+}
+impl fmt::Display for Vals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.json.is_empty() {
+            true => f.write_str("{}"),
+            false => write!(f, "{}}}", self.json),
+        }
+    }
+}
+
+/// The value of `hx-vals`.
+pub fn vals() -> Vals {
+    Vals::default()
+}
+
+/// An official htmx extension, included with [`crate::htmx::include_htmx_ext!`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Ext {
+    ClassTools,
+    HeadSupport,
+    JsonEnc,
+    LoadingStates,
+    Preload,
+    ResponseTargets,
+    Sse,
+    Ws,
+}
+impl Ext {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Ext::ClassTools => "class-tools",
+            Ext::HeadSupport => "head-support",
+            Ext::JsonEnc => "json-enc",
+            Ext::LoadingStates => "loading-states",
+            Ext::Preload => "preload",
+            Ext::ResponseTargets => "response-targets",
+            Ext::Sse => "sse",
+            Ext::Ws => "ws",
+        }
+    }
+}
+impl fmt::Display for Ext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The extensions enabled on an element and its children, for `hx-ext`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Exts(Vec<Ext>);
+impl fmt::Display for Exts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, ext) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str(ext.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+/// The value of `hx-ext`.
+///
+/// ```rust
+/// use hyperide::{hx, hyperide};
+/// let form = hyperide! {
+///     <form hx-ext={hx::ext([hx::Ext::JsonEnc, hx::Ext::LoadingStates])}></form>
+/// };
+/// assert_eq!(form, r#"<form hx-ext="json-enc, loading-states"></form>"#);
+/// ```
+pub fn ext(exts: impl IntoIterator<Item = Ext>) -> Exts {
+    Exts(exts.into_iter().collect())
+}
+
+macro_rules! impl_display_attr {
+    ($($t:ty),*) => {
+        $(
+            impl<'a> IntoAttrText<'a> for $t {
+                fn into_attr_text(self, attr: impl IntoHyperText<'a>) -> HyperText<'a> {
+                    escaped_attr_text(attr, &self.to_string())
+                }
+            }
+        )*
+    };
+}
+impl_display_attr![Url, Target, Swap, Trigger, Vals, Ext, Exts];
+
+#[cfg(test)]
+mod tests {
+    use crate::{hx, hyperide};
+
+    #[test]
+    fn escapes_quotes_in_attributes() {
+        let input = hyperide! {
+            <input
+                hx-target={hx::Target::Closest(r#"tr[data-id="1"]"#.to_string())}
+                hx-trigger={hx::trigger().event("keyup").filter(r#"key=="Enter" && ok"#)}
+                hx-swap={hx::swap(hx::SwapStyle::OuterHtml).scroll_to("#a'b", hx::ScrollPosition::Top)}
+            />
+        };
+        assert_eq!(
+            input,
+            concat!(
+                r#"<input hx-target="closest tr[data-id=&quot;1&quot;]" "#,
+                r#"hx-trigger="keyup[key==&quot;Enter&quot; &amp;&amp; ok]" "#,
+                r#"hx-swap="outerHTML scroll:#a'b:top">"#,
+            )
+        );
+    }
+
+    #[test]
+    fn escapes_json_in_vals() {
+        let vals = hx::vals().string("q", "\"<b>\" & \\").raw("page", "2");
+        assert_eq!(vals.to_string(), r#"{"q":"\"<b>\" & \\","page":2}"#);
+        let div = hyperide! { <div hx-vals={vals}></div> };
+        assert_eq!(
+            div,
+            r#"<div hx-vals="{&quot;q&quot;:&quot;\&quot;&lt;b&gt;\&quot; &amp; \\&quot;,&quot;page&quot;:2}"></div>"#
+        );
+        assert_eq!(hx::vals().to_string(), "{}");
+    }
+
+    #[test]
+    fn writes_targets_and_extensions() {
+        let targets = [
+            hx::Target::This,
+            hx::target("#list"),
+            hx::Target::Find("li".to_string()),
+            hx::Target::Next(None),
+            hx::Target::Previous(Some(".row".to_string())),
+        ];
+        let written: Vec<String> = targets.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            written,
+            ["this", "#list", "find li", "next", "previous .row"]
+        );
+        assert_eq!(hx::ext([]).to_string(), "");
+        assert_eq!(hx::ext([hx::Ext::Sse, hx::Ext::Ws]).to_string(), "sse, ws");
+    }
+}
@@ -1,3 +1,7 @@
+use std::fmt;
+
+use crate::{attr::escaped_attr_text, HyperText, IntoAttrText, IntoHyperText};
+
 #[doc(hidden)]
 pub const SCRIPT: &str = include_str!("_hyperscript.min.js");
 
@@ -13,3 +17,193 @@ macro_rules! include_hyperscript {
 }
 
 pub use include_hyperscript;
+
+/// A `<script>` tag loading hyperscript from [`crate::assets::routes`], which
+/// browsers can cache instead of downloading it inline with every page.
+#[cfg(feature = "axum")]
+#[macro_export]
+macro_rules! hyperscript_script_tag {
+    () => {
+        $crate::hyperide! {
+            <script src={$crate::assets::hyperscript_path()}></script>
+        }
+    };
+}
+
+#[cfg(feature = "axum")]
+pub use hyperscript_script_tag as script_tag;
+
+/// Starts building a hyperscript `_` attribute with values interpolated
+/// safely, instead of formatting them into the script by hand.
+///
+/// ```rust
+/// use hyperide::{hyperide, hyperscript};
+/// let url = "/notes?tag=it's";
+/// let button = hyperide! {
+///     <button _={hyperscript::script("on click fetch ")
+///         .string(url)
+///         .raw(" then put it into ")
+///         .id("note-1")}>"Load"</button>
+/// };
+/// assert_eq!(
+///     button,
+///     r#"<button _="on click fetch '/notes?tag=it\'s' then put it into #note-1">Load</button>"#,
+/// );
+/// ```
+pub fn script(raw: &str) -> Script {
+    Script::default().raw(raw)
+}
+
+/// A hyperscript script, built from raw hyperscript and interpolated values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Script {
+    source: String,
+}
+impl Script {
+    /// Appends `raw` as hyperscript, unescaped.
+    pub fn raw(mut self, raw: &str) -> Script {
+        self.source.push_str(raw);
+        self
+    }
+
+    /// Appends `value` as a hyperscript string literal.
+    pub fn string(mut self, value: &str) -> Script {
+        push_string(&mut self.source, value);
+        self
+    }
+
+    /// Appends `value` as a number.
+    ///
+    /// # Panics
+    ///
+    /// If `value` is infinite or NaN, which hyperscript has no literal for.
+    pub fn number(mut self, value: impl Into<f64>) -> Script {
+        use std::fmt::Write;
+        let value = value.into();
+        assert!(value.is_finite(), "{value} is not a hyperscript number");
+        let _ = write!(self.source, "{value}");
+        self
+    }
+
+    /// Appends a reference to the element with the id `id`, such as `#note`.
+    /// Ids which are not valid as-is are written as `#{'...'}`.
+    ///
+    /// # Panics
+    ///
+    /// If `id` contains `}`, which hyperscript cannot reference.
+    pub fn id(mut self, id: &str) -> Script {
+        self.source.push('#');
+        push_name(&mut self.source, id);
+        self
+    }
+
+    /// Appends a reference to the elements with the class `class`, such as
+    /// `.active` or tailwind's `.hover:underline`. Classes which are not valid
+    /// as-is, such as `w-1/2`, are written as `.{'...'}`.
+    ///
+    /// ```rust
+    /// use hyperide::hyperscript::script;
+    /// let toggle = script("on click toggle ").class("hover:underline");
+    /// assert_eq!(toggle.as_str(), "on click toggle .hover:underline");
+    /// let toggle = script("on click toggle ").class("w-1/2");
+    /// assert_eq!(toggle.as_str(), "on click toggle .{'w-1/2'}");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `class` contains `}`, which hyperscript cannot reference.
+    pub fn class(mut self, class: &str) -> Script {
+        self.source.push('.');
+        push_name(&mut self.source, class);
+        self
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+impl<'a> IntoAttrText<'a> for Script {
+    fn into_attr_text(self, attr: impl IntoHyperText<'a>) -> HyperText<'a> {
+        escaped_attr_text(attr, &self.source)
+    }
+}
+
+/// Writes a single quoted string, escaping what would end it early.
+fn push_string(source: &mut String, value: &str) {
+    source.push('\'');
+    for c in value.chars() {
+        match c {
+            '\'' => source.push_str("\\'"),
+            '\\' => source.push_str("\\\\"),
+            '\n' => source.push_str("\\n"),
+            '\r' => source.push_str("\\r"),
+            '\t' => source.push_str("\\t"),
+            c => source.push(c),
+        }
+    }
+    source.push('\'');
+}
+
+/// Writes an id or class name, falling back to a template when it has
+/// characters hyperscript would not read as part of the name. Templates end
+/// at the first `}`, even inside a string, so names containing it panic.
+fn push_name(source: &mut String, name: &str) {
+    assert!(
+        !name.contains('}'),
+        "`{name}` contains `}}`, which hyperscript cannot reference"
+    );
+    let plain = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | ':'));
+    if plain {
+        source.push_str(name);
+    } else {
+        source.push('{');
+        push_string(source, name);
+        source.push('}');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::script;
+
+    #[test]
+    fn writes_valid_names_as_they_are() {
+        let script = script("toggle ")
+            .class("md:hover:bg-red-500")
+            .raw(" on ")
+            .id("a_1");
+        assert_eq!(script.as_str(), "toggle .md:hover:bg-red-500 on #a_1");
+    }
+
+    #[test]
+    fn writes_other_names_as_templates() {
+        let script = script("").id("1st").raw(" ").class("it's [x]");
+        assert_eq!(script.as_str(), "#{'1st'} .{'it\\'s [x]'}");
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot reference")]
+    fn rejects_names_ending_templates() {
+        script("").class("a}b");
+    }
+
+    #[test]
+    fn writes_numbers() {
+        assert_eq!(script("wait ").number(1.5).as_str(), "wait 1.5");
+        assert_eq!(script("").number(-3).as_str(), "-3");
+    }
+
+    #[test]
+    #[should_panic(expected = "is not a hyperscript number")]
+    fn rejects_non_finite_numbers() {
+        script("").number(f64::NAN);
+    }
+}
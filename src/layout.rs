@@ -0,0 +1,182 @@
+use std::convert::Infallible;
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header::VARY, request::Parts, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use headers::HeaderMapExt;
+
+use crate::{
+    htmx::headers::request::{HxBoosted, HxHistoryRestoreRequest, HxRequest},
+    Markup,
+};
+
+/// The request headers that decide between a fragment and a full page, sent
+/// back as `Vary` so caches store both variants separately.
+const VARY_HEADERS: &str = "hx-request, hx-boosted, hx-history-restore-request";
+
+/// Extractor that decides whether a handler should respond with only its
+/// content, or with its content wrapped in the full page layout.
+///
+/// Requests made by htmx (`HX-Request: true`) only need the fragment being
+/// swapped in. Normal navigations, boosted navigations, and history restores
+/// (where htmx has a cache miss and asks for the whole page) get the full
+/// layout.
+///
+/// ```no_run
+/// # use hyperide::{hyperide, layout::Layout, Markup};
+/// # use axum::response::IntoResponse;
+/// fn base_page(content: Markup) -> Markup {
+///     hyperide! {
+///         <!DOCTYPE html>
+///         <html lang="en">
+///         <body>{ content }</body>
+///         </html>
+///     }
+/// }
+///
+/// async fn handler(layout: Layout) -> impl IntoResponse {
+///     layout.render(hyperide! { <p>"Hello"</p> }, base_page)
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    fragment: bool,
+}
+impl Layout {
+    /// Decides the layout from the htmx request headers.
+    pub fn from_headers(headers: &HeaderMap) -> Layout {
+        let is_htmx = headers.typed_get::<HxRequest>().is_some();
+        let is_boosted = headers.typed_get::<HxBoosted>().is_some();
+        let is_restore = headers.typed_get::<HxHistoryRestoreRequest>().is_some();
+        Layout {
+            fragment: is_htmx && !is_boosted && !is_restore,
+        }
+    }
+
+    /// A layout that always renders the full page.
+    pub fn full() -> Layout {
+        Layout { fragment: false }
+    }
+
+    /// A layout that always renders only the fragment.
+    pub fn fragment() -> Layout {
+        Layout { fragment: true }
+    }
+
+    /// Returns true if only the fragment should be rendered.
+    pub fn is_fragment(&self) -> bool {
+        self.fragment
+    }
+
+    /// Renders `content`, passing it through `page` first unless only the
+    /// fragment was asked for.
+    pub fn render(&self, content: Markup, page: impl FnOnce(Markup) -> Markup) -> LayoutResponse {
+        let body = if self.fragment {
+            content
+        } else {
+            page(content)
+        };
+        LayoutResponse { body }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Layout
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Layout::from_headers(&parts.headers))
+    }
+}
+
+/// A html response produced by [`Layout::render`], which carries a `Vary`
+/// header for the htmx request headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutResponse {
+    body: Markup,
+}
+impl LayoutResponse {
+    /// The rendered html.
+    pub fn as_str(&self) -> &str {
+        &self.body
+    }
+
+    /// Consumes the response, returning the rendered html.
+    pub fn into_string(self) -> String {
+        self.body.into_string()
+    }
+}
+impl IntoResponse for LayoutResponse {
+    fn into_response(self) -> Response {
+        let mut response = self.body.into_response();
+        response
+            .headers_mut()
+            .append(VARY, HeaderValue::from_static(VARY_HEADERS));
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(headers: &[(&'static str, &'static str)]) -> Layout {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.insert(*name, HeaderValue::from_static(value));
+        }
+        Layout::from_headers(&map)
+    }
+
+    fn page(content: Markup) -> Markup {
+        format!("<body>{content}</body>").into()
+    }
+
+    #[test]
+    fn plain_requests_get_the_full_page() {
+        let layout = layout(&[]);
+        assert!(!layout.is_fragment());
+        let response = layout.render("<p>Hi</p>".into(), page);
+        assert_eq!(response.as_str(), "<body><p>Hi</p></body>");
+    }
+
+    #[test]
+    fn htmx_requests_get_the_fragment() {
+        let layout = layout(&[("hx-request", "true")]);
+        assert!(layout.is_fragment());
+        let response = layout.render("<p>Hi</p>".into(), page);
+        assert_eq!(response.as_str(), "<p>Hi</p>");
+    }
+
+    #[test]
+    fn boosted_requests_get_the_full_page() {
+        let layout = layout(&[("hx-request", "true"), ("hx-boosted", "true")]);
+        assert!(!layout.is_fragment());
+    }
+
+    #[test]
+    fn history_restores_get_the_full_page() {
+        let layout = layout(&[
+            ("hx-request", "true"),
+            ("hx-history-restore-request", "true"),
+        ]);
+        assert!(!layout.is_fragment());
+    }
+
+    #[test]
+    fn responses_vary_on_htmx_headers() {
+        for layout in [Layout::full(), Layout::fragment()] {
+            let response = layout.render("<p>Hi</p>".into(), page).into_response();
+            assert_eq!(
+                response.headers()[VARY],
+                "hx-request, hx-boosted, hx-history-restore-request"
+            );
+        }
+    }
+}
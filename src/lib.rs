@@ -2,18 +2,46 @@ extern crate self as hyperide;
 
 pub use hyperide_macro::hyperide;
 
+#[cfg(all(feature = "axum", any(feature = "htmx", feature = "hyperscript")))]
+pub mod assets;
+#[cfg(all(feature = "axum", feature = "htmx"))]
+pub mod cors;
+#[cfg(feature = "htmx")]
 pub mod htmx;
+#[cfg(feature = "htmx")]
+pub mod hx;
+#[cfg(feature = "hyperscript")]
 pub mod hyperscript;
+#[cfg(all(feature = "axum", feature = "htmx"))]
+pub mod layout;
+#[cfg(feature = "htmx")]
+pub mod oob;
+#[cfg(feature = "axum")]
+pub mod route;
+#[cfg(any(feature = "vercel", feature = "lambda"))]
+pub mod serverless;
+#[cfg(feature = "axum")]
+pub mod sse;
 pub mod tailwind;
+#[cfg(feature = "vercel")]
 pub mod vercel;
+#[cfg(feature = "ws")]
+pub mod ws;
 
 mod attr;
 pub use attr::IntoAttrText;
 
+mod hash;
+
 mod hyper;
 pub use hyper::HyperText;
 pub use hyper::IntoHyperText;
 
+mod markup;
+pub use markup::Markup;
+#[cfg(feature = "axum")]
+pub use markup::WithEtag;
+
 /// Bakes css from a file into hyperide. Will insert it inside `<style>`
 /// tags and allows you to write styles in a `.css` file but include it in
 /// generated HTML without needing to serve the file separately and causing an
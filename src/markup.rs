@@ -0,0 +1,192 @@
+use std::{fmt, ops::Deref};
+
+use crate::{HyperText, IntoHyperText};
+
+/// The html rendered by [`crate::hyperide!`].
+///
+/// Derefs to `str` and converts into a `String`, so it can be used wherever
+/// the html is needed as text. With the `axum` feature it is also a response,
+/// so handlers can return `hyperide!` directly.
+///
+/// ```rust
+/// use hyperide::{hyperide, Markup};
+/// fn greeting(name: &str) -> Markup {
+///     hyperide! { <p>"Hello, "{name}"!"</p> }
+/// }
+/// assert_eq!(greeting("world"), "<p>Hello, world!</p>");
+/// let text: String = greeting("world").into();
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Markup {
+    inner: String,
+}
+impl Markup {
+    pub fn as_str(&self) -> &str {
+        &self.inner
+    }
+
+    pub fn into_string(self) -> String {
+        self.inner
+    }
+
+    /// An entity tag for the html, for the `ETag` header. It is a stable
+    /// hash of the body, so it changes whenever the body does, and is the
+    /// same for every instance of a deployment.
+    ///
+    /// ```rust
+    /// use hyperide::hyperide;
+    /// let a = hyperide! { <p>"a"</p> };
+    /// assert_eq!(a.etag(), hyperide! { <p>"a"</p> }.etag());
+    /// assert_ne!(a.etag(), hyperide! { <p>"b"</p> }.etag());
+    /// ```
+    pub fn etag(&self) -> String {
+        format!("\"{:016x}\"", crate::hash::fnv1a(self.inner.as_bytes()))
+    }
+
+    /// Responds with an `ETag` header computed from the body, as well as the
+    /// usual headers.
+    ///
+    /// ```rust
+    /// # use axum::response::IntoResponse;
+    /// use hyperide::hyperide;
+    /// async fn handler() -> impl IntoResponse {
+    ///     hyperide! { <p>"Cache me"</p> }.with_etag()
+    /// }
+    /// ```
+    #[cfg(feature = "axum")]
+    pub fn with_etag(self) -> WithEtag {
+        WithEtag { markup: self }
+    }
+}
+impl Deref for Markup {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.inner
+    }
+}
+impl AsRef<str> for Markup {
+    fn as_ref(&self) -> &str {
+        &self.inner
+    }
+}
+impl fmt::Display for Markup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.inner)
+    }
+}
+impl fmt::Debug for Markup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.inner, f)
+    }
+}
+impl From<String> for Markup {
+    fn from(inner: String) -> Self {
+        Markup { inner }
+    }
+}
+impl From<&str> for Markup {
+    fn from(value: &str) -> Self {
+        Markup {
+            inner: value.to_string(),
+        }
+    }
+}
+impl From<Markup> for String {
+    fn from(value: Markup) -> Self {
+        value.inner
+    }
+}
+impl PartialEq<str> for Markup {
+    fn eq(&self, other: &str) -> bool {
+        self.inner == other
+    }
+}
+impl PartialEq<&str> for Markup {
+    fn eq(&self, other: &&str) -> bool {
+        self.inner == *other
+    }
+}
+impl PartialEq<String> for Markup {
+    fn eq(&self, other: &String) -> bool {
+        &self.inner == other
+    }
+}
+impl FromIterator<Markup> for Markup {
+    fn from_iter<I: IntoIterator<Item = Markup>>(iter: I) -> Self {
+        Markup {
+            inner: iter.into_iter().map(|markup| markup.inner).collect(),
+        }
+    }
+}
+impl<'a> IntoHyperText<'a> for Markup {
+    fn into_hyper_text(self) -> HyperText<'a> {
+        self.inner.into()
+    }
+}
+
+/// Sets `Content-Type: text/html; charset=utf-8` and `Content-Length`.
+#[cfg(feature = "axum")]
+impl axum::response::IntoResponse for Markup {
+    fn into_response(self) -> axum::response::Response {
+        use axum::http::{
+            header::{CONTENT_LENGTH, CONTENT_TYPE},
+            HeaderValue,
+        };
+        let headers = [
+            (
+                CONTENT_TYPE,
+                HeaderValue::from_static("text/html; charset=utf-8"),
+            ),
+            (CONTENT_LENGTH, HeaderValue::from(self.inner.len())),
+        ];
+        (headers, self.inner).into_response()
+    }
+}
+
+/// A [`Markup`] response with an `ETag` header, made by [`Markup::with_etag`].
+#[cfg(feature = "axum")]
+#[derive(Debug, Clone)]
+pub struct WithEtag {
+    markup: Markup,
+}
+#[cfg(feature = "axum")]
+impl axum::response::IntoResponse for WithEtag {
+    fn into_response(self) -> axum::response::Response {
+        use axum::http::{header::ETAG, HeaderValue};
+        let etag = HeaderValue::from_str(&self.markup.etag()).expect("etag is hex");
+        let mut response = self.markup.into_response();
+        response.headers_mut().insert(ETAG, etag);
+        response
+    }
+}
+
+#[cfg(all(test, feature = "axum"))]
+mod tests {
+    use axum::{
+        http::header::{CONTENT_LENGTH, CONTENT_TYPE, ETAG},
+        response::IntoResponse,
+    };
+
+    use crate::hyperide;
+
+    #[test]
+    fn responds_with_html() {
+        let response = hyperide! { <p>"Hé"</p> }.into_response();
+        let headers = response.headers();
+        assert_eq!(headers[CONTENT_TYPE], "text/html; charset=utf-8");
+        assert_eq!(headers[CONTENT_LENGTH], "10");
+        assert!(headers.get(ETAG).is_none());
+    }
+
+    #[test]
+    fn responds_with_etag() {
+        let markup = hyperide! { <p>"Hi"</p> };
+        let etag = markup.etag();
+        let response = markup.with_etag().into_response();
+        // the same for every build, unlike `DefaultHasher`
+        assert_eq!(etag, "\"bea23a89e8fe3a19\"");
+        assert_eq!(response.headers()[ETAG], etag.as_str());
+        assert_eq!(response.headers()[CONTENT_LENGTH], "9");
+    }
+}
@@ -0,0 +1,370 @@
+use std::{error, fmt};
+
+use crate::{attr::escaped_attr_text, hx::SwapStyle, HyperText};
+
+/// A response made of a main fragment, swapped into the target as usual, and
+/// extra fragments swapped [out of band](https://htmx.org/attributes/hx-swap-oob/)
+/// into the elements on the page with the same id, such as counters or flash
+/// messages.
+///
+/// ```rust
+/// use hyperide::{hyperide, hx::SwapStyle, oob::Oob};
+/// let response = Oob::new(hyperide! { <li>"Buy milk"</li> })
+///     .with(hyperide! { <span id="count">"3"</span> })
+///     .with_swap(SwapStyle::BeforeEnd, hyperide! { <div id="flash"><p>"Added"</p></div> });
+/// assert_eq!(
+///     response.as_str(),
+///     concat!(
+///         r#"<li>Buy milk</li>"#,
+///         r#"<span id="count" hx-swap-oob="true">3</span>"#,
+///         r#"<div id="flash" hx-swap-oob="beforeend"><p>Added</p></div>"#,
+///     ),
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Oob {
+    body: String,
+}
+impl Oob {
+    /// A response with `main` as the main fragment.
+    pub fn new(main: impl Into<String>) -> Oob {
+        Oob { body: main.into() }
+    }
+
+    /// Appends `fragment`, replacing the element with the same id. Fragments
+    /// which already have an `hx-swap-oob` attribute are left as they are.
+    ///
+    /// Only the first top-level element of `fragment` is marked, so any
+    /// elements after it are swapped into the target with the main fragment.
+    /// Call this once for each element to swap out of band.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fragment` does not start with an element that has an `id`
+    /// or an `hx-swap-oob` attribute, as htmx would have nowhere to swap it.
+    /// Use [`Oob::try_with`] for fragments which are not known to be valid.
+    ///
+    /// ```should_panic
+    /// use hyperide::{hyperide, oob::Oob};
+    /// Oob::new("").with(hyperide! { <span>"3"</span> });
+    /// ```
+    pub fn with(self, fragment: impl AsRef<str>) -> Oob {
+        let fragment = fragment.as_ref();
+        self.try_with(fragment)
+            .unwrap_or_else(|e| panic!("{e}: {fragment}"))
+    }
+
+    /// Appends `fragment` like [`Oob::with`], returning an error rather than
+    /// panicking if htmx would have nowhere to swap it.
+    ///
+    /// ```rust
+    /// use hyperide::{hyperide, oob::{self, Oob}};
+    /// let error = Oob::new("").try_with(hyperide! { <span>"3"</span> }).unwrap_err();
+    /// assert_eq!(error, oob::Error::MissingId);
+    /// ```
+    pub fn try_with(self, fragment: impl AsRef<str>) -> Result<Oob, Error> {
+        self.push("true", fragment.as_ref())
+    }
+
+    /// Appends `fragment`, swapping its content into the element with the
+    /// same id using `swap`. Like [`Oob::with`], only the first top-level
+    /// element is marked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fragment` does not start with an element that has an `id`
+    /// or an `hx-swap-oob` attribute, as htmx would have nowhere to swap it.
+    /// Use [`Oob::try_with_swap`] for fragments which are not known to be
+    /// valid.
+    pub fn with_swap(self, swap: SwapStyle, fragment: impl AsRef<str>) -> Oob {
+        let fragment = fragment.as_ref();
+        self.try_with_swap(swap, fragment)
+            .unwrap_or_else(|e| panic!("{e}: {fragment}"))
+    }
+
+    /// Appends `fragment` like [`Oob::with_swap`], returning an error rather
+    /// than panicking if htmx would have nowhere to swap it.
+    pub fn try_with_swap(self, swap: SwapStyle, fragment: impl AsRef<str>) -> Result<Oob, Error> {
+        self.push(swap.as_str(), fragment.as_ref())
+    }
+
+    /// Appends `content`, swapping it into every element matching `selector`
+    /// using `swap`, rather than into an element by id. Useful for appending
+    /// to lists, such as chat messages sent over [`crate::ws`].
+    ///
+    /// The content is wrapped in a `<div>` carrying the `hx-swap-oob`
+    /// attribute, which htmx discards when swapping. Table rows and cells
+    /// cannot be parsed inside a `<div>`, so content starting with one is
+    /// wrapped in the table element that holds it inside a `<template>`
+    /// instead, which htmx 2 unwraps.
+    ///
+    /// ```rust
+    /// use hyperide::{hx::SwapStyle, oob::Oob};
+    /// let oob = Oob::default().with_selector(SwapStyle::BeforeEnd, "#messages", "<p>Hi</p>");
+    /// assert_eq!(
+    ///     oob.as_str(),
+    ///     r##"<div hx-swap-oob="beforeend:#messages"><p>Hi</p></div>"##,
+    /// );
+    /// let oob = Oob::default().with_selector(SwapStyle::BeforeEnd, "#rows", "<tr><td>1</td></tr>");
+    /// assert_eq!(
+    ///     oob.as_str(),
+    ///     r##"<template><tbody hx-swap-oob="beforeend:#rows"><tr><td>1</td></tr></tbody></template>"##,
+    /// );
+    /// ```
+    pub fn with_selector(
+        mut self,
+        swap: SwapStyle,
+        selector: &str,
+        content: impl AsRef<str>,
+    ) -> Oob {
+        let content = content.as_ref();
+        let value = format!("{}:{selector}", swap.as_str());
+        let attr: HyperText = escaped_attr_text("hx-swap-oob", &value);
+        let container = table_container(content);
+        let wrapper = container.unwrap_or("div");
+        if container.is_some() {
+            self.body.push_str("<template>");
+        }
+        self.body.push('<');
+        self.body.push_str(wrapper);
+        self.body.push(' ');
+        self.body.push_str(&attr);
+        self.body.push('>');
+        self.body.push_str(content);
+        self.body.push_str("</");
+        self.body.push_str(wrapper);
+        self.body.push('>');
+        if container.is_some() {
+            self.body.push_str("</template>");
+        }
+        self
+    }
+
+    fn push(mut self, swap: &str, fragment: &str) -> Result<Oob, Error> {
+        let tag = StartTag::parse(fragment).ok_or(Error::MissingElement)?;
+        if tag.has_swap_oob {
+            // the attribute may name a selector, in which case no id is needed
+            self.body.push_str(fragment);
+        } else if !tag.has_id {
+            return Err(Error::MissingId);
+        } else {
+            let attr: HyperText = escaped_attr_text("hx-swap-oob", swap);
+            self.body.push_str(&fragment[..tag.end]);
+            self.body.push(' ');
+            self.body.push_str(&attr);
+            self.body.push_str(&fragment[tag.end..]);
+        }
+        Ok(self)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.body
+    }
+
+    pub fn into_string(self) -> String {
+        self.body
+    }
+}
+impl From<Oob> for String {
+    fn from(oob: Oob) -> Self {
+        oob.body
+    }
+}
+#[cfg(feature = "axum")]
+impl axum::response::IntoResponse for Oob {
+    fn into_response(self) -> axum::response::Response {
+        crate::Markup::from(self.body).into_response()
+    }
+}
+
+/// Why a fragment cannot be swapped out of band.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The fragment does not start with an element.
+    MissingElement,
+    /// The element has neither an `id` nor an `hx-swap-oob` attribute.
+    MissingId,
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MissingElement => f.write_str("OOB fragment must start with an element"),
+            Error::MissingId => f.write_str("OOB fragment must have an id"),
+        }
+    }
+}
+impl error::Error for Error {}
+
+/// The element which holds `content` when it starts with part of a table,
+/// which HTML parsers drop from inside a `<div>`.
+fn table_container(content: &str) -> Option<&'static str> {
+    let name = content.trim_start().strip_prefix('<')?;
+    let end = name
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(name.len());
+    match name[..end].to_ascii_lowercase().as_str() {
+        "tr" => Some("tbody"),
+        "td" | "th" => Some("tr"),
+        "caption" | "colgroup" | "thead" | "tbody" | "tfoot" => Some("table"),
+        "col" => Some("colgroup"),
+        _ => None,
+    }
+}
+
+/// The attributes of the first start tag of a fragment that matter for OOB
+/// swaps.
+struct StartTag {
+    /// The index just after the last attribute, where more can be added.
+    end: usize,
+    has_id: bool,
+    has_swap_oob: bool,
+}
+impl StartTag {
+    fn parse(fragment: &str) -> Option<StartTag> {
+        let offset = fragment.len() - fragment.trim_start().len();
+        let bytes = fragment.as_bytes();
+        let mut i = offset;
+        if bytes.get(i) != Some(&b'<') {
+            return None;
+        }
+        i += 1;
+        let name_start = i;
+        while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'-') {
+            i += 1;
+        }
+        if i == name_start {
+            return None;
+        }
+        let mut tag = StartTag {
+            end: i,
+            has_id: false,
+            has_swap_oob: false,
+        };
+        loop {
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            match bytes.get(i)? {
+                b'>' => return Some(tag),
+                b'/' if bytes.get(i + 1) == Some(&b'>') => return Some(tag),
+                _ => {}
+            }
+            let name_start = i;
+            while i < bytes.len() && !b" \t\r\n\x0c=>/".contains(&bytes[i]) {
+                i += 1;
+            }
+            let name = &fragment[name_start..i];
+            let mut value = "";
+            if bytes.get(i) == Some(&b'=') {
+                i += 1;
+                let (start, stop) = match bytes.get(i)? {
+                    quote @ (b'"' | b'\'') => {
+                        let start = i + 1;
+                        let len = fragment[start..].find(*quote as char)?;
+                        i = start + len + 1;
+                        (start, start + len)
+                    }
+                    _ => {
+                        let start = i;
+                        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>'
+                        {
+                            i += 1;
+                        }
+                        (start, i)
+                    }
+                };
+                value = &fragment[start..stop];
+            } else if i == name_start {
+                // a stray `/` which is not the end of the tag
+                i += 1;
+                continue;
+            }
+            if name.eq_ignore_ascii_case("id") && !value.is_empty() {
+                tag.has_id = true;
+            }
+            if name.eq_ignore_ascii_case("hx-swap-oob")
+                || name.eq_ignore_ascii_case("data-hx-swap-oob")
+            {
+                tag.has_swap_oob = true;
+            }
+            tag.end = i;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(fragment: &str) -> (bool, bool, &str) {
+        let tag = StartTag::parse(fragment).unwrap();
+        (tag.has_id, tag.has_swap_oob, &fragment[..tag.end])
+    }
+
+    #[test]
+    fn parses_quoted_and_unquoted_values() {
+        assert_eq!(parse(r#"<p id="a">x</p>"#), (true, false, r#"<p id="a""#));
+        assert_eq!(parse("<p id='a'>x</p>"), (true, false, "<p id='a'"));
+        assert_eq!(parse("<p id=a>x</p>"), (true, false, "<p id=a"));
+        assert_eq!(
+            parse(r#"<p title="a > b" id=a class='c'>x</p>"#),
+            (true, false, r#"<p title="a > b" id=a class='c'"#)
+        );
+    }
+
+    #[test]
+    fn parses_self_closing_tags() {
+        assert_eq!(parse(r#"<img id="a"/>"#), (true, false, r#"<img id="a""#));
+        assert_eq!(parse(r#"<img id="a" />"#), (true, false, r#"<img id="a""#));
+        assert_eq!(parse("<br/>"), (false, false, "<br"));
+    }
+
+    #[test]
+    fn skips_leading_whitespace() {
+        assert_eq!(parse("\n  <p id=a></p>"), (true, false, "\n  <p id=a"));
+    }
+
+    #[test]
+    fn finds_swap_oob_attributes() {
+        assert!(parse(r#"<li hx-swap-oob="beforeend:#list">x</li>"#).1);
+        assert!(parse(r#"<li data-hx-swap-oob="true" id="a">x</li>"#).1);
+    }
+
+    #[test]
+    fn requires_non_empty_ids() {
+        assert!(!parse("<p>x</p>").0);
+        assert!(!parse(r#"<p id="">x</p>"#).0);
+        assert!(!parse("<p id>x</p>").0);
+        assert!(!parse(r#"<p data-id="a">x</p>"#).0);
+    }
+
+    #[test]
+    fn rejects_fragments_without_elements() {
+        assert!(StartTag::parse("text").is_none());
+        assert!(StartTag::parse("< p>").is_none());
+        assert!(StartTag::parse("<p id=\"a\"").is_none());
+        assert_eq!(Oob::new("").try_with("text"), Err(Error::MissingElement));
+        assert_eq!(Oob::new("").try_with("<p>x</p>"), Err(Error::MissingId));
+    }
+
+    #[test]
+    fn keeps_selector_swaps_without_ids() {
+        let fragment = r#"<li hx-swap-oob="beforeend:#list">x</li>"#;
+        let oob = Oob::new("<p>main</p>").try_with(fragment).unwrap();
+        assert_eq!(oob.as_str(), format!("<p>main</p>{fragment}"));
+    }
+
+    #[test]
+    fn wraps_table_parts_for_selector_swaps() {
+        let oob = Oob::default()
+            .with_selector(SwapStyle::AfterBegin, "#row", " <TD>1</TD>")
+            .with_selector(SwapStyle::BeforeEnd, "#list", "<li>2</li>");
+        assert_eq!(
+            oob.as_str(),
+            concat!(
+                r##"<template><tr hx-swap-oob="afterbegin:#row"> <TD>1</TD></tr></template>"##,
+                r##"<div hx-swap-oob="beforeend:#list"><li>2</li></div>"##,
+            )
+        );
+    }
+}
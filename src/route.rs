@@ -0,0 +1,106 @@
+use std::fmt::{Display, Write};
+
+use axum::{body::HttpBody, routing::MethodRouter, Router};
+
+use crate::{attr::escaped_attr_text, HyperText, IntoHyperText};
+
+pub use hyperide_macro::TypedRoute;
+
+/// A struct describing the path of an axum route, which can be both
+/// registered on a [`Router`] with [`RouterExt::typed_route`] and linked to
+/// from templates, keeping the two in sync. Derive this with
+/// [`derive@TypedRoute`].
+pub trait TypedRoute {
+    /// The axum path of the route, such as `/hello/:name`.
+    const PATH: &'static str;
+
+    /// The url of this instance of the route, with each parameter filled in
+    /// from the struct's fields.
+    fn to_url(&self) -> String;
+}
+
+/// Adds [`RouterExt::typed_route`] to [`Router`].
+pub trait RouterExt<S, B> {
+    /// Adds a route at the path of `R`.
+    ///
+    /// ```no_run
+    /// # use axum::{extract::Path, routing::get, Router};
+    /// use hyperide::route::{RouterExt, TypedRoute};
+    /// #[derive(TypedRoute, serde::Deserialize)]
+    /// #[route("/hello/:name")]
+    /// struct Hello {
+    ///     name: String,
+    /// }
+    /// async fn hello(Path(Hello { name }): Path<Hello>) -> String {
+    ///     format!("Hello, {name}!")
+    /// }
+    /// let app: Router = Router::new().typed_route::<Hello>(get(hello));
+    /// ```
+    fn typed_route<R: TypedRoute>(self, method_router: MethodRouter<S, B>) -> Self;
+}
+impl<S, B> RouterExt<S, B> for Router<S, B>
+where
+    B: HttpBody + Send + 'static,
+    S: Clone + Send + Sync + 'static,
+{
+    fn typed_route<R: TypedRoute>(self, method_router: MethodRouter<S, B>) -> Self {
+        self.route(R::PATH, method_router)
+    }
+}
+
+#[doc(hidden)]
+pub fn url_attr_text<'a>(attr: impl IntoHyperText<'a>, route: &impl TypedRoute) -> HyperText<'a> {
+    escaped_attr_text(attr, &route.to_url())
+}
+
+/// Percent encodes a `:param` of a path.
+#[doc(hidden)]
+pub fn encode_segment(value: &impl Display) -> String {
+    encode(value, b"")
+}
+
+/// Percent encodes a `*wildcard` of a path, which may contain `/`.
+#[doc(hidden)]
+pub fn encode_wildcard(value: &impl Display) -> String {
+    encode(value, b"/")
+}
+
+fn encode(value: &impl Display, also: &[u8]) -> String {
+    let value = value.to_string();
+    let mut encoded = String::with_capacity(value.len());
+    for &b in value.as_bytes() {
+        // unreserved characters and sub-delims, which are allowed in segments
+        if b.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@".contains(&b) || also.contains(&b) {
+            encoded.push(b as char);
+        } else {
+            let _ = write!(encoded, "%{b:02X}");
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_segment, encode_wildcard};
+
+    #[test]
+    fn encodes_segments() {
+        assert_eq!(encode_segment(&"Ferris Crab"), "Ferris%20Crab");
+        assert_eq!(encode_segment(&"a/b?c#d"), "a%2Fb%3Fc%23d");
+        assert_eq!(encode_segment(&"100%"), "100%25");
+        assert_eq!(encode_segment(&"café"), "caf%C3%A9");
+        assert_eq!(encode_segment(&42), "42");
+    }
+
+    #[test]
+    fn keeps_characters_allowed_in_segments() {
+        let allowed = "AZaz09-._~!$&'()*+,;=:@";
+        assert_eq!(encode_segment(&allowed), allowed);
+    }
+
+    #[test]
+    fn keeps_slashes_in_wildcards() {
+        assert_eq!(encode_wildcard(&"docs/a b/c?"), "docs/a%20b/c%3F");
+        assert_eq!(encode_wildcard(&"/leading"), "/leading");
+    }
+}
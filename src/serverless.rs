@@ -0,0 +1,137 @@
+//! Runs an axum router as a serverless function, translating the platform's
+//! requests and responses with a [`ServerlessAdapter`].
+//!
+//! [`crate::vercel::Vercel`] is the adapter for vercel, and, with the
+//! `lambda` feature, [`lambda::Lambda`] is the adapter for AWS Lambda behind
+//! API Gateway or a function URL.
+
+use std::{error, fmt};
+
+use axum::{
+    body::{Body as AxumBody, HttpBody},
+    http::{Request as HttpRequest, Response as HttpResponse, StatusCode},
+    Router,
+};
+use tower_service::Service;
+
+#[cfg(feature = "lambda")]
+pub mod lambda;
+
+/// Translates between the requests and responses of a serverless platform
+/// and the http requests and responses of axum.
+pub trait ServerlessAdapter {
+    /// The request the platform invokes the function with.
+    type Request;
+    /// The response the function returns to the platform.
+    type Response;
+    /// What is needed from the request to respond to it, such as the format
+    /// of the event. The default is used to respond to requests which could
+    /// not be translated.
+    type Context: Default;
+
+    /// Translates a request from the platform into a request for the app.
+    fn request(
+        &self,
+        request: Self::Request,
+    ) -> Result<(HttpRequest<AxumBody>, Self::Context), RequestError>;
+
+    /// Translates the app's response, with its body read, into a response
+    /// for the platform.
+    fn response(&self, response: HttpResponse<Vec<u8>>, context: Self::Context) -> Self::Response;
+}
+
+/// Responds to a request from the platform with `app`. Requests which could
+/// not be translated, and responses whose body could not be read, are logged
+/// with `tracing` and answered with an error status.
+pub async fn handle<A: ServerlessAdapter>(
+    adapter: &A,
+    app: &mut Router,
+    request: A::Request,
+) -> A::Response {
+    let (request, context) = match adapter.request(request) {
+        Ok(translated) => translated,
+        Err(e) => {
+            tracing::warn!("{e}");
+            return adapter.response(e.response(), A::Context::default());
+        }
+    };
+
+    // Call the axum router
+    let response = match app.call(request).await {
+        Ok(response) => response,
+        Err(e) => match e {},
+    };
+
+    let (parts, mut body) = response.into_parts();
+    let mut buffer = Vec::new();
+    while let Some(bytes) = body.data().await {
+        match bytes {
+            Ok(bytes) => buffer.extend(bytes),
+            Err(e) => {
+                let e = RequestError::new(StatusCode::INTERNAL_SERVER_ERROR, e);
+                tracing::warn!("{e}");
+                return adapter.response(e.response(), context);
+            }
+        }
+    }
+    adapter.response(HttpResponse::from_parts(parts, buffer), context)
+}
+
+/// Why a request could not be handled, and the status to respond with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestError {
+    status: StatusCode,
+    message: String,
+}
+impl RequestError {
+    pub fn new(status: StatusCode, message: impl fmt::Display) -> RequestError {
+        RequestError {
+            status,
+            message: message.to_string(),
+        }
+    }
+
+    /// A `400 Bad Request` error.
+    pub fn bad_request(message: impl fmt::Display) -> RequestError {
+        RequestError::new(StatusCode::BAD_REQUEST, message)
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// The response sent for the error, which only contains the status, so
+    /// the message does not leak to clients.
+    fn response(&self) -> HttpResponse<Vec<u8>> {
+        let mut response = HttpResponse::new(self.status.to_string().into_bytes());
+        *response.status_mut() = self.status;
+        response
+    }
+}
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+impl error::Error for RequestError {}
+
+/// Whether a content type is text, such as html, css, javascript and json,
+/// which platforms can send without base64 encoding it.
+pub(crate) fn is_text(content_type: &str) -> bool {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let Some((kind, subtype)) = mime.split_once('/') else {
+        return false;
+    };
+    kind == "text"
+        || subtype.ends_with("+json")
+        || subtype.ends_with("+xml")
+        || matches!(
+            subtype,
+            "json" | "javascript" | "ecmascript" | "xml" | "x-www-form-urlencoded"
+        )
+}
@@ -0,0 +1,445 @@
+//! An adapter for AWS Lambda functions invoked by API Gateway REST APIs
+//! (payload format 1.0), HTTP APIs (1.0 or 2.0), or function URLs (2.0).
+//!
+//! Events and responses are JSON values, so the adapter can be used with any
+//! Lambda runtime client, such as `lambda_runtime`:
+//!
+//! ```ignore
+//! use hyperide::serverless::lambda::Lambda;
+//! use lambda_runtime::{service_fn, LambdaEvent};
+//!
+//! let app = axum::Router::new();
+//! lambda_runtime::run(service_fn(|event: LambdaEvent<serde_json::Value>| {
+//!     let mut app = app.clone();
+//!     async move { Ok::<_, lambda_runtime::Error>(Lambda::new().handle(&mut app, event.payload).await) }
+//! }))
+//! .await
+//! ```
+
+use axum::{
+    body::Body as AxumBody,
+    http::{
+        header::{CONTENT_TYPE, COOKIE, SET_COOKIE},
+        HeaderName, HeaderValue, Method, Request as HttpRequest, Response as HttpResponse,
+    },
+    Router,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde_json::{json, Map, Value};
+use url::form_urlencoded;
+
+use super::{is_text, RequestError, ServerlessAdapter};
+
+/// The largest request Lambda accepts, 6 MB, which includes the rest of the
+/// event.
+const MAX_BODY_SIZE: usize = 6_291_556;
+
+/// The payload format of an event, which the response must match.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// Version 1.0, sent by REST APIs, and HTTP APIs configured for it.
+    V1,
+    /// Version 2.0, sent by HTTP APIs and function URLs.
+    #[default]
+    V2,
+}
+
+/// Translates API Gateway and function URL events into requests for an axum
+/// router, and its responses back.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Lambda {
+    max_body_size: usize,
+}
+impl Default for Lambda {
+    fn default() -> Self {
+        Lambda {
+            max_body_size: MAX_BODY_SIZE,
+        }
+    }
+}
+impl Lambda {
+    /// Accepts bodies as large as Lambda does.
+    pub fn new() -> Lambda {
+        Lambda::default()
+    }
+
+    /// The largest request body, in bytes, passed to the app. Larger
+    /// requests are refused with `413 Payload Too Large`. Defaults to 6 MB,
+    /// the most Lambda accepts.
+    pub fn max_body_size(mut self, size: usize) -> Lambda {
+        self.max_body_size = size;
+        self
+    }
+
+    /// Responds to an event with `app`.
+    pub async fn handle(&self, app: &mut Router, event: Value) -> Value {
+        super::handle(self, app, event).await
+    }
+}
+impl ServerlessAdapter for Lambda {
+    type Request = Value;
+    type Response = Value;
+    type Context = Format;
+
+    fn request(&self, event: Value) -> Result<(HttpRequest<AxumBody>, Format), RequestError> {
+        let format = if event["version"] == "2.0" {
+            Format::V2
+        } else if event["httpMethod"].is_string() {
+            Format::V1
+        } else {
+            return Err(RequestError::bad_request(
+                "not an API Gateway or function URL event",
+            ));
+        };
+
+        let mut request = HttpRequest::builder();
+        match format {
+            Format::V1 => {
+                request = request
+                    .method(method(&event["httpMethod"])?)
+                    .uri(v1_uri(&event));
+                // multi value headers hold every value, where they are sent
+                let headers = match &event["multiValueHeaders"] {
+                    headers @ Value::Object(_) => headers,
+                    _ => &event["headers"],
+                };
+                for (name, values) in object(headers) {
+                    for value in values_of(values) {
+                        request = request.header(header_name(name)?, header_value(value)?);
+                    }
+                }
+            }
+            Format::V2 => {
+                let query = event["rawQueryString"].as_str().unwrap_or_default();
+                let path = event["rawPath"].as_str().unwrap_or("/");
+                let uri = if query.is_empty() {
+                    path.to_string()
+                } else {
+                    format!("{path}?{query}")
+                };
+                request = request
+                    .method(method(&event["requestContext"]["http"]["method"])?)
+                    .uri(uri);
+                for (name, value) in object(&event["headers"]) {
+                    let value = value.as_str().unwrap_or_default();
+                    request = request.header(header_name(name)?, header_value(value)?);
+                }
+                // cookies are sent separately from the other headers
+                let cookies: Vec<&str> = values_of(&event["cookies"]).collect();
+                if !cookies.is_empty() {
+                    request = request.header(COOKIE, header_value(&cookies.join("; "))?);
+                }
+            }
+        }
+
+        let body = match event["body"].as_str() {
+            None => Vec::new(),
+            Some(body) if event["isBase64Encoded"] == true => STANDARD
+                .decode(body)
+                .map_err(|e| RequestError::bad_request(format!("invalid base64 body: {e}")))?,
+            Some(body) => body.as_bytes().to_vec(),
+        };
+        if body.len() > self.max_body_size {
+            return Err(RequestError::new(
+                axum::http::StatusCode::PAYLOAD_TOO_LARGE,
+                format!(
+                    "body of {} bytes is over the {} byte limit",
+                    body.len(),
+                    self.max_body_size
+                ),
+            ));
+        }
+
+        let request = request
+            .body(AxumBody::from(body))
+            .map_err(RequestError::bad_request)?;
+        Ok((request, format))
+    }
+
+    fn response(&self, response: HttpResponse<Vec<u8>>, format: Format) -> Value {
+        let (parts, body) = response.into_parts();
+        let content_type = parts
+            .headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        let (body, base64) = match String::from_utf8(body) {
+            Ok(text) if is_text(content_type) || text.is_empty() => (text, false),
+            Ok(text) => (STANDARD.encode(text), true),
+            Err(e) => (STANDARD.encode(e.into_bytes()), true),
+        };
+
+        let mut response = json!({
+            "statusCode": parts.status.as_u16(),
+            "body": body,
+            "isBase64Encoded": base64,
+        });
+        let values = |name: &HeaderName| {
+            parts
+                .headers
+                .get_all(name)
+                .iter()
+                .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+        };
+        match format {
+            Format::V1 => {
+                let headers: Map<String, Value> = parts
+                    .headers
+                    .keys()
+                    .map(|name| (name.to_string(), values(name).collect()))
+                    .collect();
+                response["multiValueHeaders"] = Value::Object(headers);
+            }
+            Format::V2 => {
+                let headers: Map<String, Value> = parts
+                    .headers
+                    .keys()
+                    .filter(|name| *name != SET_COOKIE)
+                    .map(|name| {
+                        let value: Vec<String> = values(name).collect();
+                        (name.to_string(), Value::String(value.join(",")))
+                    })
+                    .collect();
+                response["headers"] = Value::Object(headers);
+                // cookies are returned separately, as they cannot be joined
+                let cookies: Vec<String> = values(&SET_COOKIE).collect();
+                if !cookies.is_empty() {
+                    response["cookies"] = json!(cookies);
+                }
+            }
+        }
+        response
+    }
+}
+
+/// The fields of an object, or none if it is missing.
+fn object(value: &Value) -> impl Iterator<Item = (&String, &Value)> {
+    value.as_object().into_iter().flatten()
+}
+
+fn method(method: &Value) -> Result<Method, RequestError> {
+    let method = method.as_str().unwrap_or_default();
+    Method::from_bytes(method.as_bytes())
+        .map_err(|_| RequestError::bad_request(format!("invalid method `{method}`")))
+}
+
+fn header_name(name: &str) -> Result<HeaderName, RequestError> {
+    HeaderName::from_bytes(name.as_bytes())
+        .map_err(|_| RequestError::bad_request(format!("invalid header name `{name}`")))
+}
+
+fn header_value(value: &str) -> Result<HeaderValue, RequestError> {
+    HeaderValue::from_str(value)
+        .map_err(|_| RequestError::bad_request(format!("invalid header value `{value}`")))
+}
+
+/// The strings in a value which is either a string or an array of them.
+fn values_of(value: &Value) -> impl Iterator<Item = &str> {
+    let values = match value {
+        Value::Array(values) => values.as_slice(),
+        value => std::slice::from_ref(value),
+    };
+    values.iter().filter_map(Value::as_str)
+}
+
+/// The uri of a 1.0 event, which only has the decoded path and query
+/// parameters.
+fn v1_uri(event: &Value) -> String {
+    let params = match &event["multiValueQueryStringParameters"] {
+        Value::Object(_) => &event["multiValueQueryStringParameters"],
+        _ => &event["queryStringParameters"],
+    };
+    let mut query = form_urlencoded::Serializer::new(String::new());
+    for (name, values) in object(params) {
+        for value in values_of(values) {
+            query.append_pair(name, value);
+        }
+    }
+    let query = query.finish();
+    let path = crate::route::encode_wildcard(&event["path"].as_str().unwrap_or("/"));
+    if query.is_empty() {
+        path
+    } else {
+        format!("{path}?{query}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        http::{HeaderMap, StatusCode, Uri},
+        response::{AppendHeaders, IntoResponse},
+        routing::{get, post},
+    };
+
+    use super::*;
+
+    fn fixture(name: &str) -> Value {
+        let json = match name {
+            "rest-api" => include_str!("fixtures/rest-api.json"),
+            "http-api-v1" => include_str!("fixtures/http-api-v1.json"),
+            "http-api-v2" => include_str!("fixtures/http-api-v2.json"),
+            "function-url" => include_str!("fixtures/function-url.json"),
+            _ => unreachable!(),
+        };
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn app() -> Router {
+        async fn echo(
+            uri: Uri,
+            method: Method,
+            headers: HeaderMap,
+            body: String,
+        ) -> impl IntoResponse {
+            let cookie = headers
+                .get(COOKIE)
+                .map(|v| v.to_str().unwrap().to_string())
+                .unwrap_or_default();
+            (
+                AppendHeaders([(SET_COOKIE, "a=1"), (SET_COOKIE, "b=2")]),
+                format!("{method} {uri} cookie={cookie} body={body}"),
+            )
+        }
+        Router::new()
+            .route("/hello/:name", get(echo).post(echo))
+            .route(
+                "/image",
+                post(|| async { ([(CONTENT_TYPE, "image/png")], vec![0x89u8, b'P']) }),
+            )
+    }
+
+    async fn handle(event: Value) -> Value {
+        Lambda::new().handle(&mut app(), event).await
+    }
+
+    fn run<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn rest_api_event() {
+        let (request, format) = Lambda::new().request(fixture("rest-api")).unwrap();
+        assert_eq!(format, Format::V1);
+        assert_eq!(request.method(), Method::GET);
+        assert_eq!(request.uri(), "/hello/world?lang=en&tag=a&tag=b+c");
+        assert_eq!(request.headers().get_all("accept").iter().count(), 2);
+
+        let response = run(handle(fixture("rest-api")));
+        assert_eq!(response["statusCode"], 200);
+        assert_eq!(response["isBase64Encoded"], false);
+        assert_eq!(
+            response["body"],
+            "GET /hello/world?lang=en&tag=a&tag=b+c cookie=session=abc body="
+        );
+        assert_eq!(
+            response["multiValueHeaders"]["set-cookie"],
+            json!(["a=1", "b=2"])
+        );
+    }
+
+    #[test]
+    fn http_api_v1_event() {
+        let (request, format) = Lambda::new().request(fixture("http-api-v1")).unwrap();
+        assert_eq!(format, Format::V1);
+        assert_eq!(request.method(), Method::POST);
+        assert_eq!(request.uri(), "/hello/world");
+
+        let response = run(handle(fixture("http-api-v1")));
+        assert_eq!(
+            response["body"],
+            "POST /hello/world cookie= body=name=hyperide"
+        );
+    }
+
+    #[test]
+    fn http_api_v2_event() {
+        let (request, format) = Lambda::new().request(fixture("http-api-v2")).unwrap();
+        assert_eq!(format, Format::V2);
+        assert_eq!(request.uri(), "/hello/world?lang=en&tag=a&tag=b");
+
+        let response = run(handle(fixture("http-api-v2")));
+        assert_eq!(
+            response["body"],
+            "GET /hello/world?lang=en&tag=a&tag=b cookie=session=abc; theme=dark body="
+        );
+        assert_eq!(response["cookies"], json!(["a=1", "b=2"]));
+        assert!(response["headers"].get("set-cookie").is_none());
+        assert_eq!(
+            response["headers"]["content-type"],
+            "text/plain; charset=utf-8"
+        );
+    }
+
+    #[test]
+    fn function_url_event() {
+        let response = run(handle(fixture("function-url")));
+        assert_eq!(response["statusCode"], 200);
+        // the base64 body is decoded for the app
+        assert_eq!(response["body"], "POST /hello/world cookie= body={\"n\":1}");
+    }
+
+    #[test]
+    fn binary_response_is_base64() {
+        let mut event = fixture("function-url");
+        event["rawPath"] = json!("/image");
+        let response = run(handle(event));
+        assert_eq!(response["isBase64Encoded"], true);
+        assert_eq!(response["body"], STANDARD.encode([0x89u8, b'P']));
+        assert_eq!(response["headers"]["content-type"], "image/png");
+    }
+
+    #[test]
+    fn invalid_events_are_bad_requests() {
+        let e = Lambda::new()
+            .request(json!({ "source": "aws.events" }))
+            .unwrap_err();
+        assert_eq!(e.status(), StatusCode::BAD_REQUEST);
+
+        let mut event = fixture("function-url");
+        event["body"] = json!("not base64!");
+        let e = Lambda::new().request(event).unwrap_err();
+        assert_eq!(e.status(), StatusCode::BAD_REQUEST);
+
+        let response = run(handle(json!({ "version": "2.0" })));
+        assert_eq!(response["statusCode"], 400);
+    }
+
+    #[test]
+    fn large_body_is_too_large() {
+        let e = Lambda::new()
+            .max_body_size(4)
+            .request(fixture("http-api-v1"))
+            .unwrap_err();
+        assert_eq!(e.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn encodes_v1_paths() {
+        let mut event = fixture("rest-api");
+        event["path"] = json!("/hello/wo rld");
+        let (request, _) = Lambda::new().request(event).unwrap();
+        assert_eq!(request.uri().path(), "/hello/wo%20rld");
+    }
+
+    #[test]
+    fn encodes_percent_in_v1_paths() {
+        // requested as `/a%2541` and `/100%25`
+        for (path, encoded) in [("/a%41", "/a%2541"), ("/100%", "/100%25")] {
+            let mut event = fixture("rest-api");
+            event["path"] = json!(path);
+            let (request, _) = Lambda::new().request(event).unwrap();
+            assert_eq!(request.uri().path(), encoded);
+        }
+    }
+
+    #[test]
+    fn not_found_is_passed_through() {
+        let mut event = fixture("http-api-v2");
+        event["rawPath"] = json!("/missing");
+        assert_eq!(run(handle(event))["statusCode"], 404);
+    }
+}
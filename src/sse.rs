@@ -0,0 +1,181 @@
+//! Server sent events carrying `hyperide!` fragments, for the htmx
+//! [sse extension](https://htmx.org/extensions/server-sent-events/).
+//!
+//! ```no_run
+//! # use axum::response::IntoResponse;
+//! use hyperide::{hyperide, sse};
+//! async fn notifications() -> impl IntoResponse {
+//!     let notifications = futures_util::stream::iter(["Saved", "Published"]);
+//!     sse::stream(futures_util::StreamExt::map(notifications, |text| {
+//!         ("notification", hyperide! { <p class="toast">{text}</p> })
+//!     }))
+//! }
+//! ```
+
+use std::convert::Infallible;
+
+use axum::{
+    body::HttpBody,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
+};
+use futures_util::{Stream, StreamExt};
+
+/// Responds with each `(event_name, fragment)` as a server sent event,
+/// sending a keep-alive comment whenever the stream is idle so proxies do not
+/// close the connection.
+///
+/// The event name is what `sse-swap` listens for on the page.
+pub fn stream<S, N, M>(events: S) -> Sse<impl Stream<Item = Result<Event, Infallible>>>
+where
+    S: Stream<Item = (N, M)> + Send + 'static,
+    N: AsRef<str> + 'static,
+    M: AsRef<str> + 'static,
+{
+    Sse::new(events.map(to_event)).keep_alive(KeepAlive::default())
+}
+
+fn to_event<N, M>((name, markup): (N, M)) -> Result<Event, Infallible>
+where
+    N: AsRef<str>,
+    M: AsRef<str>,
+{
+    Ok(event(name, markup))
+}
+
+/// An event called `name` with `markup` as its data, split over a `data:`
+/// line for every line of the markup.
+///
+/// # Panics
+///
+/// Panics if `name` contains a line break, as it cannot be sent over SSE.
+pub fn event(name: impl AsRef<str>, markup: impl AsRef<str>) -> Event {
+    Event::default()
+        .event(name)
+        .data(normalize_newlines(markup.as_ref()))
+}
+
+/// A server sent event received by [`collect`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Message {
+    /// The event name, or `None` for the default `message` event.
+    pub event: Option<String>,
+    /// The `data:` lines of the event, joined with newlines.
+    pub data: String,
+}
+
+/// Frames `events` the same way as [`stream`] and parses the response body
+/// back into messages, the way a browser would. Use this to test SSE handlers
+/// without a server or browser. Keep-alives are left out, so no timer is
+/// needed to run this.
+///
+/// ```rust
+/// use hyperide::{hyperide, sse};
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let events = futures_util::stream::iter([
+///     ("count", hyperide! { <span>"1"</span> }),
+///     ("count", hyperide! { <span>"2"</span> }),
+/// ]);
+/// let messages = sse::collect(events).await;
+/// assert_eq!(messages.len(), 2);
+/// assert_eq!(messages[1].event.as_deref(), Some("count"));
+/// assert_eq!(messages[1].data, "<span>2</span>");
+/// # });
+/// ```
+pub async fn collect<S, N, M>(events: S) -> Vec<Message>
+where
+    S: Stream<Item = (N, M)> + Send + 'static,
+    N: AsRef<str> + 'static,
+    M: AsRef<str> + 'static,
+{
+    let mut body = Sse::new(events.map(to_event)).into_response().into_body();
+    let mut text = String::new();
+    while let Some(Ok(chunk)) = body.data().await {
+        text.push_str(&String::from_utf8_lossy(&chunk));
+    }
+    parse(&text)
+}
+
+/// Parses a server sent event stream, skipping comments such as keep-alives.
+fn parse(text: &str) -> Vec<Message> {
+    let mut messages = Vec::new();
+    let mut event = None;
+    let mut data: Option<String> = None;
+    for line in text.split('\n') {
+        if line.is_empty() {
+            // a blank line dispatches the event, unless it has no data
+            if let Some(data) = data.take() {
+                messages.push(Message {
+                    event: event.take(),
+                    data,
+                });
+            }
+            event = None;
+            continue;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => event = Some(value.to_string()),
+            "data" => match &mut data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => data = Some(value.to_string()),
+            },
+            _ => {}
+        }
+    }
+    messages
+}
+
+/// SSE can only carry `\n` line breaks inside data, so `\r\n` and lone `\r`
+/// are converted.
+fn normalize_newlines(s: &str) -> String {
+    s.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn collect_one(markup: &'static str) -> Message {
+        let mut messages = collect(futures_util::stream::iter([("update", markup)])).await;
+        assert_eq!(messages.len(), 1);
+        messages.remove(0)
+    }
+
+    #[tokio::test]
+    async fn sends_multi_line_data() {
+        let message = collect_one("<ul>\n<li>One</li>\n</ul>").await;
+        assert_eq!(message.event.as_deref(), Some("update"));
+        assert_eq!(message.data, "<ul>\n<li>One</li>\n</ul>");
+    }
+
+    #[tokio::test]
+    async fn normalizes_carriage_returns() {
+        let message = collect_one("<ul>\r\n<li>One</li>\r</ul>\r\n").await;
+        assert_eq!(message.data, "<ul>\n<li>One</li>\n</ul>\n");
+    }
+
+    #[tokio::test]
+    async fn keeps_leading_spaces() {
+        let message = collect_one(" <b>a</b>\n  <i>b</i>").await;
+        assert_eq!(message.data, " <b>a</b>\n  <i>b</i>");
+    }
+
+    #[test]
+    fn parses_comments_and_default_events() {
+        let messages = parse(":keep-alive\n\ndata: one\ndata:two\n\nevent:skipped\n\n");
+        assert_eq!(
+            messages,
+            [Message {
+                event: None,
+                data: "one\ntwo".to_string(),
+            }]
+        );
+    }
+}
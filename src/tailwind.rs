@@ -1,33 +1,74 @@
-use std::path::Path;
+#[cfg(feature = "tailwind")]
+mod build;
+#[cfg(feature = "tailwind")]
+#[allow(deprecated)]
+pub use build::{bootstrap, Error, Tailwind};
+#[cfg(feature = "axum")]
+pub mod dev;
+#[cfg(feature = "tailwind")]
+pub mod scan;
 
-/// Build script to generate tailwind output css on project build. Config should
-/// be the path of `tailwind.config.js`, and input should be the path of
-/// `input.css`.
-pub fn bootstrap(config: &Path, input: &Path) {
-    let tw_bin = std::env::var("TAILWINDCSS_BIN").unwrap_or_else(|_| "tailwind".to_string());
-    let tw_out = format!("{}/tailwind.out.css", std::env::var("OUT_DIR").unwrap());
-    let tw_proc = std::process::Command::new(tw_bin)
-        .arg("-c")
-        .arg(config)
-        .arg("-i")
-        .arg(input)
-        .arg("-o")
-        .arg(tw_out)
-        .arg("--minify")
-        .status()
-        .expect("Tailwind Did Not Run Successfully");
-    if !tw_proc.success() {
-        panic!("Tailwind Did Not Run Successfully: {tw_proc}");
+/// Joins the classes whose condition is true, for `class` attributes with
+/// optional classes. Literal classes passed to this are found by [`scan`].
+///
+/// ```rust
+/// use hyperide::tailwind::classes;
+/// let active = true;
+/// assert_eq!(
+///     classes([("rounded px-2", true), ("bg-blue-500", active), ("hidden", false)]),
+///     "rounded px-2 bg-blue-500",
+/// );
+/// ```
+pub fn classes<'a>(classes: impl IntoIterator<Item = (&'a str, bool)>) -> String {
+    let mut joined = String::new();
+    for (class, enabled) in classes {
+        if enabled && !class.is_empty() {
+            if !joined.is_empty() {
+                joined.push(' ');
+            }
+            joined.push_str(class);
+        }
     }
+    joined
 }
 
 /// A variant of [`crate::include_style!`] that explicitly includes the tailwind
-/// output generated by [`bootstrap`]
+/// output generated by [`Tailwind`]. With no arguments this is the default
+/// `tailwind` output, otherwise the output with the given
+/// [`Tailwind::output_name`].
+///
+/// Once [`dev::routes`] is watching tailwind, which needs the `tailwind-dev`
+/// feature, this links to the live stylesheet instead, with the reload script.
+///
+/// ```ignore
+/// use hyperide::{hyperide, include_tailwind};
+/// let head = hyperide! {
+///     <head>
+///         {include_tailwind!("admin")}
+///     </head>
+/// };
+/// ```
 #[macro_export]
 macro_rules! include_tailwind {
     () => {
-        $crate::include_style!(concat!(env!("OUT_DIR"), "/tailwind.out.css"))
+        $crate::include_tailwind!("tailwind")
     };
+    ($name:literal $(,)?) => {
+        $crate::tailwind::stylesheet(
+            $name,
+            $crate::include_style!(concat!(env!("OUT_DIR"), "/", $name, ".out.css")),
+        )
+    };
+}
+
+#[doc(hidden)]
+pub fn stylesheet(name: &str, baked: crate::Markup) -> crate::Markup {
+    #[cfg(feature = "tailwind-dev")]
+    if dev::is_watching() {
+        return dev::tags(name);
+    }
+    let _ = name;
+    baked
 }
 
 pub use include_tailwind;
@@ -0,0 +1,323 @@
+use std::{
+    collections::BTreeSet,
+    env, error, fmt, fs, io,
+    path::{Path, PathBuf},
+    process::{Command, ExitStatus},
+};
+
+use super::scan;
+
+/// Why tailwind could not generate the stylesheet.
+#[derive(Debug)]
+pub enum Error {
+    /// An environment variable set by cargo was missing, because this was not
+    /// run from a build script.
+    MissingEnv(&'static str),
+    /// Sources could not be scanned, or generated files could not be written.
+    Io(io::Error),
+    /// The tailwind binary could not be run, usually because it is not
+    /// installed.
+    Spawn { bin: String, source: io::Error },
+    /// Tailwind ran, but failed.
+    Failed { bin: String, status: ExitStatus },
+    /// A content glob was not a valid pattern.
+    Glob {
+        glob: String,
+        source: glob::PatternError,
+    },
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MissingEnv(var) => {
+                write!(f, "{var} is not set, tailwind must be run from build.rs")
+            }
+            Error::Io(e) => write!(f, "could not prepare tailwind: {e}"),
+            Error::Spawn { bin, source } => write!(
+                f,
+                "could not run `{bin}`, is tailwind installed? set TAILWIND_BIN to its path: {source}"
+            ),
+            Error::Failed { bin, status } => write!(f, "`{bin}` failed: {status}"),
+            Error::Glob { glob, source } => write!(f, "invalid content glob `{glob}`: {source}"),
+        }
+    }
+}
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(e) | Error::Spawn { source: e, .. } => Some(e),
+            Error::Glob { source, .. } => Some(source),
+            Error::MissingEnv(_) | Error::Failed { .. } => None,
+        }
+    }
+}
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+/// Generates a tailwind stylesheet from a build script, for
+/// [`include_tailwind!`] to include.
+///
+/// As well as the content in the config, the classes found by [`scan`] in the
+/// crate's Rust sources are written to a safelist that tailwind reads, so
+/// classes chosen inside blocks are included.
+///
+/// Cargo is told to rerun the build script when the config, the input, or any
+/// scanned source changes. Other files in the config's `content` are not
+/// tracked.
+///
+/// If tailwind fails, such as when it is not installed, the error is emitted
+/// as a cargo warning and the stylesheet from the last successful run is kept,
+/// or an empty one is written. The crate still builds, so the returned error
+/// can be ignored to allow building without tailwind.
+///
+/// The binary is `tailwind`, unless `TAILWIND_BIN` or `TAILWINDCSS_BIN` is
+/// set to its path.
+///
+/// ```no_run
+/// // in build.rs
+/// use hyperide::tailwind::Tailwind;
+/// let _ = Tailwind::new()
+///     .config("tailwind.config.js")
+///     .input("tailwind.in.css")
+///     .content_globs(["src/**/*.rs"])
+///     .run();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tailwind {
+    config: PathBuf,
+    input: PathBuf,
+    output_name: String,
+    minify: bool,
+    content_globs: Vec<String>,
+}
+impl Default for Tailwind {
+    fn default() -> Self {
+        Tailwind {
+            config: PathBuf::from("tailwind.config.js"),
+            input: PathBuf::from("tailwind.in.css"),
+            output_name: "tailwind".to_string(),
+            minify: true,
+            content_globs: Vec::new(),
+        }
+    }
+}
+impl Tailwind {
+    /// A build using `tailwind.config.js` and `tailwind.in.css`, minifying
+    /// the output and scanning every Rust source in the crate.
+    pub fn new() -> Tailwind {
+        Tailwind::default()
+    }
+
+    /// The path of `tailwind.config.js`.
+    pub fn config(mut self, config: impl Into<PathBuf>) -> Tailwind {
+        self.config = config.into();
+        self
+    }
+
+    /// The path of the input css, with the `@tailwind` directives.
+    pub fn input(mut self, input: impl Into<PathBuf>) -> Tailwind {
+        self.input = input.into();
+        self
+    }
+
+    /// The name of the stylesheet, written to `OUT_DIR/{name}.out.css` and
+    /// included with `include_tailwind!("{name}")`. Defaults to `tailwind`,
+    /// which `include_tailwind!()` includes.
+    ///
+    /// Build scripts can run several builds with different names, such as a
+    /// separate config for an admin area:
+    ///
+    /// ```no_run
+    /// // in build.rs
+    /// use hyperide::tailwind::Tailwind;
+    /// let _ = Tailwind::new()
+    ///     .config("tailwind.config.js")
+    ///     .content_globs(["src/**/*.rs"])
+    ///     .run();
+    /// let _ = Tailwind::new()
+    ///     .config("admin/tailwind.config.js")
+    ///     .input("admin/tailwind.in.css")
+    ///     .output_name("admin")
+    ///     .content_globs(["src/admin/**/*.rs"])
+    ///     .run();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is empty or is not a plain file name, such as
+    /// `admin/site`.
+    pub fn output_name(mut self, name: impl Into<String>) -> Tailwind {
+        let name = name.into();
+        assert!(
+            !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\']),
+            "tailwind output name must be a plain file name: {name:?}"
+        );
+        self.output_name = name;
+        self
+    }
+
+    /// Whether to minify the stylesheet. Defaults to `true`.
+    pub fn minify(mut self, minify: bool) -> Tailwind {
+        self.minify = minify;
+        self
+    }
+
+    /// Globs of the Rust sources to scan for classes, relative to the crate
+    /// root, such as `src/**/*.rs`. By default every `.rs` file outside
+    /// `target` and hidden directories is scanned.
+    pub fn content_globs<G: Into<String>>(
+        mut self,
+        globs: impl IntoIterator<Item = G>,
+    ) -> Tailwind {
+        self.content_globs = globs.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Runs tailwind, writing the stylesheet to `OUT_DIR`.
+    pub fn run(&self) -> Result<(), Error> {
+        let out_dir = PathBuf::from(env::var_os("OUT_DIR").ok_or(Error::MissingEnv("OUT_DIR"))?);
+        let tw_out = out_dir.join(format!("{}.out.css", self.output_name));
+        let result = self.generate(&out_dir, &tw_out);
+        if let Err(e) = &result {
+            println!("cargo:warning=tailwind: {e}");
+            if !tw_out.exists() {
+                println!(
+                    "cargo:warning=tailwind: no stylesheet was generated, pages will be unstyled"
+                );
+                fs::write(&tw_out, "")?;
+            } else {
+                println!(
+                    "cargo:warning=tailwind: using the stylesheet from the last successful build"
+                );
+            }
+        }
+        result
+    }
+
+    fn generate(&self, out_dir: &Path, tw_out: &Path) -> Result<(), Error> {
+        println!("cargo:rerun-if-env-changed=TAILWIND_BIN");
+        println!("cargo:rerun-if-env-changed=TAILWINDCSS_BIN");
+        println!("cargo:rerun-if-changed={}", self.config.display());
+        println!("cargo:rerun-if-changed={}", self.input.display());
+        let tw_bin = bin();
+        let manifest_dir = PathBuf::from(
+            env::var_os("CARGO_MANIFEST_DIR").ok_or(Error::MissingEnv("CARGO_MANIFEST_DIR"))?,
+        );
+
+        let sources = self.sources(&manifest_dir)?;
+        let mut classes = BTreeSet::new();
+        let mut dirs = BTreeSet::new();
+        for source in &sources {
+            println!("cargo:rerun-if-changed={}", source.display());
+            if let Some(dir) = source.parent() {
+                dirs.insert(dir);
+            }
+            // files which fail to parse are skipped, as rustc will report them
+            if let Ok(found) = scan::scan_source(&fs::read_to_string(source)?) {
+                classes.extend(found);
+            }
+        }
+        // so that new sources are scanned
+        for dir in dirs {
+            println!("cargo:rerun-if-changed={}", dir.display());
+        }
+
+        let name = &self.output_name;
+        let safelist = out_dir.join(format!("{name}.safelist.txt"));
+        scan::write_safelist(&classes, &safelist)?;
+        let tw_config = out_dir.join(format!("{name}.config.js"));
+        fs::write(&tw_config, wrapped_config(&self.config, &safelist))?;
+        // for `tailwind --watch` in dev mode
+        let input = self
+            .input
+            .canonicalize()
+            .unwrap_or_else(|_| self.input.clone());
+        fs::write(
+            out_dir.join(format!("{name}.input.txt")),
+            input.display().to_string(),
+        )?;
+
+        // written elsewhere first, so a failed run keeps the last stylesheet
+        let tw_tmp = out_dir.join(format!("{name}.out.css.tmp"));
+        let mut command = Command::new(&tw_bin);
+        command
+            .arg("-c")
+            .arg(tw_config)
+            .arg("-i")
+            .arg(&self.input)
+            .arg("-o")
+            .arg(&tw_tmp);
+        if self.minify {
+            command.arg("--minify");
+        }
+        let status = command.status().map_err(|source| Error::Spawn {
+            bin: tw_bin.clone(),
+            source,
+        })?;
+        if !status.success() {
+            return Err(Error::Failed {
+                bin: tw_bin,
+                status,
+            });
+        }
+        fs::rename(tw_tmp, tw_out)?;
+        Ok(())
+    }
+
+    /// The Rust sources to scan.
+    fn sources(&self, manifest_dir: &Path) -> Result<Vec<PathBuf>, Error> {
+        if self.content_globs.is_empty() {
+            return Ok(scan::rust_files(manifest_dir)?);
+        }
+        let mut sources = BTreeSet::new();
+        for content_glob in &self.content_globs {
+            let pattern = manifest_dir.join(content_glob);
+            let paths = glob::glob(&pattern.to_string_lossy()).map_err(|source| Error::Glob {
+                glob: content_glob.clone(),
+                source,
+            })?;
+            for path in paths {
+                let path = path.map_err(io::Error::from)?;
+                if path.is_file() {
+                    sources.insert(path);
+                }
+            }
+        }
+        Ok(sources.into_iter().collect())
+    }
+}
+
+/// Runs [`Tailwind`] with `config` and `input`.
+#[deprecated(note = "use `Tailwind::new().config(config).input(input).run()`")]
+pub fn bootstrap(config: &Path, input: &Path) -> Result<(), Error> {
+    Tailwind::new().config(config).input(input).run()
+}
+
+/// The tailwind binary, from `TAILWIND_BIN` or `TAILWINDCSS_BIN`.
+pub(crate) fn bin() -> String {
+    env::var("TAILWIND_BIN")
+        .or_else(|_| env::var("TAILWINDCSS_BIN"))
+        .unwrap_or_else(|_| "tailwind".to_string())
+}
+
+/// A tailwind config which extends the user's config with the safelist as
+/// content.
+fn wrapped_config(config: &Path, safelist: &Path) -> String {
+    let config = config
+        .canonicalize()
+        .unwrap_or_else(|_| config.to_path_buf());
+    format!(
+        r#"const config = require({config:?});
+const content = Array.isArray(config.content) ? {{ files: config.content }} : {{ ...config.content }};
+module.exports = {{
+  ...config,
+  content: {{ ...content, files: [...(content.files || []), {safelist:?}] }},
+}};
+"#,
+        config = config.display().to_string(),
+        safelist = safelist.display().to_string(),
+    )
+}
@@ -0,0 +1,240 @@
+//! Development mode for tailwind, behind the `tailwind-dev` feature.
+//!
+//! With the feature enabled, [`routes`] runs `tailwind --watch` for every
+//! stylesheet generated by [`super::Tailwind`]. From then on
+//! [`crate::include_tailwind!`] links to the stylesheet on disk instead of
+//! baking it in, along with a script that reloads the page when a stylesheet
+//! changes or the server restarts, such as when `cargo watch` rebuilds it
+//! after a template changes.
+//!
+//! Without the feature [`routes`] is empty and `include_tailwind!` always
+//! bakes the stylesheet in, so the same code can be used in release builds.
+//! Watcher failures are logged with `tracing`.
+//!
+//! ```no_run
+//! # use axum::Router;
+//! let app: Router = Router::new()
+//!     .merge(hyperide::tailwind::dev::routes(env!("OUT_DIR")));
+//! ```
+
+use std::path::PathBuf;
+
+use axum::Router;
+
+/// The path of the server sent events which tell the page to reload.
+pub const RELOAD_PATH: &str = "/_hyperide/livereload";
+
+/// Routes serving the stylesheets in `out_dir`, which should be
+/// `env!("OUT_DIR")` of the crate whose build script runs
+/// [`super::Tailwind`], and the live reload events. Starts a tailwind watcher
+/// for each stylesheet, so this must be called inside a tokio runtime.
+///
+/// Returns no routes without the `tailwind-dev` feature.
+pub fn routes<S>(out_dir: impl Into<PathBuf>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    #[cfg(feature = "tailwind-dev")]
+    {
+        watch::routes(out_dir.into())
+    }
+    #[cfg(not(feature = "tailwind-dev"))]
+    {
+        let _ = out_dir;
+        Router::new()
+    }
+}
+
+#[cfg(feature = "tailwind-dev")]
+pub(crate) use watch::is_watching;
+#[cfg(feature = "tailwind-dev")]
+pub use watch::tags;
+
+#[cfg(feature = "tailwind-dev")]
+mod watch {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+        process::Stdio,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        time::{Duration, SystemTime},
+    };
+
+    use axum::{
+        extract,
+        http::{
+            header::{CACHE_CONTROL, CONTENT_TYPE},
+            StatusCode,
+        },
+        response::{IntoResponse, Response},
+        routing::get,
+        Router,
+    };
+    use tokio::{process::Command, sync::broadcast};
+
+    use super::RELOAD_PATH;
+    use crate::Markup;
+
+    const CSS_PATH: &str = "/_hyperide/tailwind";
+
+    const RELOAD_SCRIPT: &str = r#"(() => {
+  let lost = false;
+  const source = new EventSource("/_hyperide/livereload");
+  source.addEventListener("reload", () => location.reload());
+  source.onerror = () => { lost = true; };
+  source.onopen = () => { if (lost) location.reload(); };
+})();"#;
+
+    /// Set once [`super::routes`] has started watching tailwind.
+    static WATCHING: AtomicBool = AtomicBool::new(false);
+
+    /// Whether [`super::routes`] is serving the live stylesheets, so pages
+    /// should link to them.
+    pub(crate) fn is_watching() -> bool {
+        WATCHING.load(Ordering::Relaxed)
+    }
+
+    /// A link to the stylesheet called `name` served by [`super::routes`],
+    /// and the live reload script. This is what [`crate::include_tailwind!`]
+    /// includes once the routes are watching tailwind.
+    pub fn tags(name: &str) -> Markup {
+        let href = format!("{CSS_PATH}/{name}.css");
+        format!(
+            r#"<link rel="stylesheet" href="{}"><script>{RELOAD_SCRIPT}</script>"#,
+            html_escape::encode_double_quoted_attribute(&href)
+        )
+        .into()
+    }
+
+    pub(super) fn routes<S>(out_dir: PathBuf) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let bin = crate::tailwind::build::bin();
+        for name in outputs(&out_dir) {
+            tokio::spawn(watch(bin.clone(), out_dir.clone(), name));
+        }
+        let (reload, _) = broadcast::channel(16);
+        tokio::spawn(poll(out_dir.clone(), reload.clone()));
+        WATCHING.store(true, Ordering::Relaxed);
+
+        let out_dir = Arc::new(out_dir);
+        Router::new()
+            .route(
+                &format!("{CSS_PATH}/:file"),
+                get(move |extract::Path(file): extract::Path<String>| css(out_dir.clone(), file)),
+            )
+            .route(RELOAD_PATH, get(move || live_reload(reload.subscribe())))
+    }
+
+    /// The names of the stylesheets generated in `out_dir`.
+    fn outputs(out_dir: &Path) -> Vec<String> {
+        let Ok(entries) = fs::read_dir(out_dir) else {
+            tracing::warn!("{} does not exist", out_dir.display());
+            return Vec::new();
+        };
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                Some(name.strip_suffix(".input.txt")?.to_string())
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    async fn watch(bin: String, out_dir: PathBuf, name: String) {
+        let input = match fs::read_to_string(out_dir.join(format!("{name}.input.txt"))) {
+            Ok(input) => input,
+            Err(e) => return tracing::warn!("could not watch {name}: {e}"),
+        };
+        let child = Command::new(&bin)
+            .arg("-c")
+            .arg(out_dir.join(format!("{name}.config.js")))
+            .arg("-i")
+            .arg(input.trim_end())
+            .arg("-o")
+            .arg(out_dir.join(format!("{name}.out.css")))
+            .arg("--watch")
+            // tailwind stops watching when stdin closes, which also stops it
+            // when the server exits without dropping the child
+            .stdin(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn();
+        match child {
+            Ok(mut child) => {
+                let _ = child.wait().await;
+                tracing::warn!("`{bin} --watch` for {name} exited");
+            }
+            Err(e) => tracing::error!("could not run `{bin}`, is tailwind installed? {e}"),
+        }
+    }
+
+    /// Sends a reload whenever a stylesheet in `out_dir` is written.
+    async fn poll(out_dir: PathBuf, reload: broadcast::Sender<()>) {
+        let mut last = modified(&out_dir);
+        let mut interval = tokio::time::interval(Duration::from_millis(250));
+        loop {
+            interval.tick().await;
+            let now = modified(&out_dir);
+            if now != last {
+                last = now;
+                let _ = reload.send(());
+            }
+        }
+    }
+
+    fn modified(out_dir: &Path) -> Vec<(PathBuf, Option<SystemTime>)> {
+        let mut modified: Vec<_> = fs::read_dir(out_dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let is_css = path.to_str()?.ends_with(".out.css");
+                is_css.then(|| {
+                    let time = fs::metadata(&path).and_then(|m| m.modified()).ok();
+                    (path, time)
+                })
+            })
+            .collect();
+        modified.sort();
+        modified
+    }
+
+    async fn css(out_dir: Arc<PathBuf>, file: String) -> Response {
+        let Some(name) = file
+            .strip_suffix(".css")
+            .filter(|name| !name.is_empty() && !name.starts_with('.'))
+        else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+        match tokio::fs::read_to_string(out_dir.join(format!("{name}.out.css"))).await {
+            Ok(css) => (
+                [
+                    (CONTENT_TYPE, "text/css; charset=utf-8"),
+                    (CACHE_CONTROL, "no-store"),
+                ],
+                css,
+            )
+                .into_response(),
+            Err(_) => StatusCode::NOT_FOUND.into_response(),
+        }
+    }
+
+    async fn live_reload(receiver: broadcast::Receiver<()>) -> impl IntoResponse {
+        let reloads = futures_util::stream::unfold(receiver, |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    // browsers skip events without data
+                    Ok(()) => return Some((("reload", "reload"), receiver)),
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+        crate::sse::stream(reloads)
+    }
+}
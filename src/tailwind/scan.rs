@@ -0,0 +1,269 @@
+//! Finds the tailwind classes used by `hyperide!` invocations by parsing Rust
+//! sources, rather than relying on tailwind's regex scanning, which misses
+//! classes chosen inside blocks.
+//!
+//! Classes are collected from:
+//!
+//! - `class` attributes, both literal and every string literal inside a block
+//! - `.class` references in literal hyperscript `_` attributes
+//! - string literals passed to [`super::classes`] and
+//!   [`crate::hyperscript::Script::class`]
+
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use syn::{
+    visit::{self, Visit},
+    Expr, ExprCall, ExprMethodCall, Lit, LitStr, Macro,
+};
+
+/// Scans the Rust source of a file, returning the classes it uses.
+pub fn scan_source(source: &str) -> syn::Result<BTreeSet<String>> {
+    let file = syn::parse_file(source)?;
+    let mut scanner = Scanner::default();
+    scanner.visit_file(&file);
+    Ok(scanner.classes)
+}
+
+/// Scans every `.rs` file in `dir` and its subdirectories, skipping `target`
+/// and hidden directories. Files which fail to parse are skipped, as rustc
+/// will report them.
+pub fn scan_dir(dir: &Path) -> io::Result<BTreeSet<String>> {
+    let mut classes = BTreeSet::new();
+    for file in rust_files(dir)? {
+        let source = fs::read_to_string(&file)?;
+        if let Ok(found) = scan_source(&source) {
+            classes.extend(found);
+        }
+    }
+    Ok(classes)
+}
+
+/// Every `.rs` file in `dir` and its subdirectories, skipping `target` and
+/// hidden directories.
+pub fn rust_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if path.is_dir() {
+                if name != "target" && !name.starts_with('.') {
+                    dirs.push(path);
+                }
+            } else if path.extension().is_some_and(|ext| ext == "rs") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Writes `classes` to `path`, one per line, for tailwind to read as
+/// content. The file is left untouched if it would not change, so tailwind
+/// and cargo do not see it as modified.
+pub fn write_safelist(classes: &BTreeSet<String>, path: &Path) -> io::Result<()> {
+    let mut content = String::new();
+    for class in classes {
+        content.push_str(class);
+        content.push('\n');
+    }
+    if fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+        return Ok(());
+    }
+    fs::write(path, content)
+}
+
+#[derive(Default)]
+struct Scanner {
+    classes: BTreeSet<String>,
+}
+impl Scanner {
+    fn add_classes(&mut self, classes: &str) {
+        self.classes
+            .extend(classes.split_whitespace().map(str::to_string));
+    }
+
+    fn add_hyperscript(&mut self, script: &str) {
+        for word in script.split_whitespace() {
+            let Some(class) = word.strip_prefix('.') else {
+                continue;
+            };
+            // `.{'...'}` templates and member access are not classes
+            if class.starts_with(|c: char| c.is_ascii_alphabetic() || c == '-' || c == '_') {
+                self.classes.insert(class.to_string());
+            }
+        }
+    }
+
+    /// Adds every string literal in `tokens` as classes.
+    fn add_literals(&mut self, tokens: TokenStream) {
+        for tree in tokens {
+            match tree {
+                TokenTree::Literal(lit) => {
+                    if let Some(value) = lit_str(&lit) {
+                        self.add_classes(&value);
+                    }
+                }
+                TokenTree::Group(group) => self.add_literals(group.stream()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Adds every string literal in `expr` as classes.
+    fn add_strings(&mut self, expr: &Expr) {
+        struct Strings<'a>(&'a mut Scanner);
+        impl<'ast> Visit<'ast> for Strings<'_> {
+            fn visit_lit_str(&mut self, lit: &'ast LitStr) {
+                self.0.add_classes(&lit.value());
+            }
+        }
+        Strings(self).visit_expr(expr);
+    }
+
+    /// Walks the tokens of a macro, which syn does not parse, looking for
+    /// attributes, class helpers, and nested `hyperide!` invocations.
+    fn scan_tokens(&mut self, tokens: TokenStream) {
+        let trees: Vec<TokenTree> = tokens.into_iter().collect();
+        for (i, tree) in trees.iter().enumerate() {
+            let next = trees.get(i + 1);
+            let after = trees.get(i + 2);
+            match tree {
+                TokenTree::Ident(ident) => {
+                    let is_attr = matches!(next, Some(TokenTree::Punct(p)) if p.as_char() == '=');
+                    match (ident.to_string().as_str(), after) {
+                        ("class", Some(TokenTree::Literal(lit))) if is_attr => {
+                            if let Some(value) = lit_str(lit) {
+                                self.add_classes(&value);
+                            }
+                        }
+                        ("class", Some(TokenTree::Group(group)))
+                            if is_attr && group.delimiter() == Delimiter::Brace =>
+                        {
+                            self.add_literals(group.stream());
+                        }
+                        ("_", Some(TokenTree::Literal(lit))) if is_attr => {
+                            if let Some(value) = lit_str(lit) {
+                                self.add_hyperscript(&value);
+                            }
+                        }
+                        ("classes" | "class", _) => {
+                            if let Some(TokenTree::Group(group)) = next {
+                                if group.delimiter() == Delimiter::Parenthesis {
+                                    self.add_literals(group.stream());
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                TokenTree::Group(group) => self.scan_tokens(group.stream()),
+                _ => {}
+            }
+        }
+    }
+}
+impl<'ast> Visit<'ast> for Scanner {
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        self.scan_tokens(mac.tokens.clone());
+        visit::visit_macro(self, mac);
+    }
+
+    fn visit_expr_call(&mut self, call: &'ast ExprCall) {
+        if let Expr::Path(path) = &*call.func {
+            if path
+                .path
+                .segments
+                .last()
+                .is_some_and(|s| s.ident == "classes")
+            {
+                for arg in &call.args {
+                    self.add_strings(arg);
+                }
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        if call.method == "class" {
+            for arg in &call.args {
+                self.add_strings(arg);
+            }
+        }
+        visit::visit_expr_method_call(self, call);
+    }
+}
+
+fn lit_str(lit: &proc_macro2::Literal) -> Option<String> {
+    match Lit::new(lit.clone()) {
+        Lit::Str(lit) => Some(lit.value()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(source: &str) -> Vec<String> {
+        scan_source(source).unwrap().into_iter().collect()
+    }
+
+    #[test]
+    fn finds_literal_classes() {
+        let source = r#"
+            fn page() -> String {
+                hyperide! { <div class="flex  p-4"><p class="text-sm">"Hi"</p></div> }
+            }
+        "#;
+        assert_eq!(scan(source), ["flex", "p-4", "text-sm"]);
+    }
+
+    #[test]
+    fn finds_classes_in_blocks() {
+        let source = r#"
+            fn item(done: bool) -> String {
+                hyperide! {
+                    <li class={if done { "line-through" } else { "font-bold" }}>
+                        {hyperide! { <span class="ml-2">"x"</span> }}
+                    </li>
+                }
+            }
+        "#;
+        assert_eq!(scan(source), ["font-bold", "line-through", "ml-2"]);
+    }
+
+    #[test]
+    fn finds_hyperscript_and_helper_classes() {
+        let source = r#"
+            fn button(active: bool) -> String {
+                let class = classes([("rounded", true), ("bg-blue-500", active)]);
+                let script = hyperscript::script("on click toggle ").class("hidden");
+                hyperide! { <button class={class} _="on click add .opacity-50 to me">"Go"</button> }
+            }
+        "#;
+        assert_eq!(
+            scan(source),
+            ["bg-blue-500", "hidden", "opacity-50", "rounded"]
+        );
+    }
+
+    #[test]
+    fn ignores_other_strings() {
+        let source = r#"
+            fn page() -> String {
+                let title = "Not a class";
+                hyperide! { <h1 id="title">{title}</h1> }
+            }
+        "#;
+        assert!(scan(source).is_empty());
+    }
+}
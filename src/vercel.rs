@@ -1,53 +1,609 @@
-use std::ops::Deref;
+use std::{error, fmt, net::SocketAddr};
 
-use axum::{body::Body as AxumBody, body::HttpBody, http::Request as HttpRequest, Router};
-use http::Uri;
-use tower_service::Service;
-use url::Url;
+use axum::{
+    body::Body as AxumBody,
+    body::HttpBody,
+    http::{header::CONTENT_TYPE, HeaderMap, Request as HttpRequest, Response as HttpResponse},
+    middleware,
+    response::{IntoResponse, Response as AxumResponse},
+    Router, ServiceExt,
+};
+use http::{
+    uri::{self, InvalidUri, InvalidUriParts, PathAndQuery},
+    StatusCode, Uri,
+};
+use tower_layer::Layer;
+use url::form_urlencoded;
 use vercel_runtime::{run as vercel_run, Body, Error, Request, Response};
 
+use crate::serverless::{self, is_text, RequestError, ServerlessAdapter};
+
+/// The function every request is rewritten to.
+const FUNCTION_PATH: &str = "/api/main";
+
+/// The largest request body vercel functions accept, 4.5 MB.
+const MAX_BODY_SIZE: usize = 4_500_000;
+
+/// How `vercel.json` rewrites requests to the function.
+///
+/// The default matches a `{ "source": "/:path(.*)", "destination": "/api/main" }`
+/// rewrite. For a deployment which only rewrites part of the site, such as an
+/// app under `/app` in a monorepo, set the base path the rewrite's source
+/// starts with, and the name of its path parameter:
+///
+/// ```rust
+/// // { "source": "/app/:rest(.*)", "destination": "/api/main" }
+/// let rewrite = hyperide::vercel::Rewrite::new()
+///     .param("rest")
+///     .base_path("/app");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rewrite {
+    param: String,
+    base_path: String,
+}
+impl Default for Rewrite {
+    fn default() -> Self {
+        Rewrite {
+            param: "path".to_string(),
+            base_path: String::new(),
+        }
+    }
+}
+impl Rewrite {
+    /// The rewrite of every path to the function in `path`.
+    pub fn new() -> Rewrite {
+        Rewrite::default()
+    }
+
+    /// The name of the query parameter the rewrite puts the path in, which
+    /// is the name of the parameter in the rewrite's source. Defaults to
+    /// `path`.
+    pub fn param(mut self, name: impl Into<String>) -> Rewrite {
+        self.param = name.into();
+        self
+    }
+
+    /// The path the rewrite's source starts with, which is put back in front
+    /// of the path parameter. Defaults to the root.
+    pub fn base_path(mut self, path: impl AsRef<str>) -> Rewrite {
+        let path = path.as_ref().trim_matches('/');
+        self.base_path = if path.is_empty() {
+            String::new()
+        } else {
+            format!("/{path}")
+        };
+        self
+    }
+}
+
 /// Runs an axum router in the vercel runtime, responding to a request
 pub async fn run(app: Router) -> Result<(), Error> {
-    let handler = |mut req: Request| {
-        let mut app = app.clone();
-        async move {
-            // Get supplied path from vercel rewrite
-            let url = Url::parse(&req.uri().to_string()).unwrap();
-            let path = url
-                .query_pairs()
-                .find_map(|(k, v)| (k == "path").then_some(v))
-                .unwrap();
-
-            // Compute original path based on path uri
-            let new_path = format!("/{}?{}", path, req.uri().query().unwrap_or_default())
-                .parse()
-                .unwrap();
-
-            // Alter request URI to match
-            let mut uri = req.uri().clone().into_parts();
-            let path_and_query = uri.path_and_query.as_mut().unwrap();
-            *path_and_query = new_path;
-            *req.uri_mut() = Uri::from_parts(uri).unwrap();
-
-            // Convert into Axum/Http Request
-            let (req_parts, req_body) = req.into_parts();
-            let req_body = req_body.deref().to_owned();
-            let req = HttpRequest::from_parts(req_parts, AxumBody::from(req_body));
-
-            // Call the axum router
-            let initial_response = app.call(req).await?;
-
-            // Convert back into aws lambda response
-            let (parts, mut initial_body) = initial_response.into_parts();
-            let mut buffer = Vec::new();
-            while let Some(bytes) = initial_body.data().await {
-                let bytes = bytes?;
-                buffer.extend(bytes);
+    Vercel::new().run(app).await
+}
+
+/// Runs an axum router in the vercel runtime, for a deployment with a custom
+/// [`Rewrite`].
+pub async fn run_with(app: Router, rewrite: Rewrite) -> Result<(), Error> {
+    Vercel::new().rewrite(rewrite).run(app).await
+}
+
+/// Runs an axum router on vercel, or locally with [`Vercel::serve_local`],
+/// with options for how requests reach it. This is the
+/// [`ServerlessAdapter`] for vercel.
+///
+/// ```no_run
+/// # use axum::Router;
+/// # async fn serve(app: Router) -> Result<(), vercel_runtime::Error> {
+/// use hyperide::vercel::{Rewrite, Vercel};
+/// Vercel::new()
+///     .rewrite(Rewrite::new().base_path("/app"))
+///     .max_body_size(1_000_000)
+///     .run(app)
+///     .await
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Vercel {
+    rewrite: Rewrite,
+    max_body_size: usize,
+}
+impl Default for Vercel {
+    fn default() -> Self {
+        Vercel {
+            rewrite: Rewrite::default(),
+            max_body_size: MAX_BODY_SIZE,
+        }
+    }
+}
+impl Vercel {
+    /// Runs with the default [`Rewrite`], accepting bodies as large as
+    /// vercel does.
+    pub fn new() -> Vercel {
+        Vercel::default()
+    }
+
+    /// How `vercel.json` rewrites requests to the function.
+    pub fn rewrite(mut self, rewrite: Rewrite) -> Vercel {
+        self.rewrite = rewrite;
+        self
+    }
+
+    /// The largest request body, in bytes, passed to the app. Larger
+    /// requests are refused with `413 Payload Too Large`. Defaults to 4.5 MB,
+    /// the most vercel accepts.
+    pub fn max_body_size(mut self, size: usize) -> Vercel {
+        self.max_body_size = size;
+        self
+    }
+
+    /// Runs an axum router in the vercel runtime.
+    ///
+    /// The runtime cannot stream, so responses are sent once the app has
+    /// finished them. Responses with a textual content type are sent as
+    /// text, and the rest are base64 encoded by the runtime.
+    pub async fn run(self, app: Router) -> Result<(), Error> {
+        let handler = |req: Request| {
+            let mut app = app.clone();
+            let vercel = self.clone();
+            async move { Ok::<_, Error>(serverless::handle(&vercel, &mut app, req).await) }
+        };
+        vercel_run(handler).await
+    }
+
+    /// Serves an axum router on a local server, rewriting every request the
+    /// way `vercel.json` does and then undoing it the way [`Vercel::run`]
+    /// does, so the app sees the same requests as it would on vercel.
+    /// Requests outside the base path are not found, as vercel would not
+    /// send them to the function.
+    ///
+    /// Unlike on vercel, responses are streamed.
+    pub async fn serve_local(self, app: Router, addr: SocketAddr) -> Result<(), Error> {
+        // a layer around the router rewrites before routing, unlike `Router::layer`
+        let app = middleware::map_request(move |req: HttpRequest<AxumBody>| {
+            let vercel = self.clone();
+            async move {
+                let result = match vercel_rewrite(req.uri(), &vercel.rewrite) {
+                    Some(rewritten) => local_request(req, rewritten, &vercel).await,
+                    None => return Err(StatusCode::NOT_FOUND.into_response()),
+                };
+                result.map_err(|e| {
+                    tracing::warn!("{e}");
+                    e.into_response()
+                })
             }
-            let final_response = Response::from_parts(parts, Body::Binary(buffer));
+        })
+        .layer(app);
+        axum::Server::bind(&addr)
+            .serve(app.into_make_service())
+            .await?;
+        Ok(())
+    }
+}
+
+impl ServerlessAdapter for Vercel {
+    type Request = Request;
+    type Response = Response<Body>;
+    type Context = ();
+
+    fn request(&self, req: Request) -> Result<(HttpRequest<AxumBody>, ()), RequestError> {
+        let req = translate_request(req, self)?;
+        Ok((req, ()))
+    }
 
-            Ok(final_response)
+    fn response(&self, response: HttpResponse<Vec<u8>>, _: ()) -> Response<Body> {
+        let (parts, body) = response.into_parts();
+        let body = response_body(&parts.headers, body);
+        Response::from_parts(parts, body)
+    }
+}
+
+/// Serves an axum router on a local server, rewriting every request the way
+/// the `/:path(.*)` rewrite to `/api/main` in `vercel.json` does and then
+/// undoing it the way [`run`] does, so the app sees the same requests as it
+/// would on vercel.
+///
+/// ```no_run
+/// # use axum::Router;
+/// # async fn serve(app: Router) -> Result<(), vercel_runtime::Error> {
+/// if std::env::var_os("VERCEL").is_some() {
+///     hyperide::vercel::run(app).await
+/// } else {
+///     hyperide::vercel::serve_local(app, ([127, 0, 0, 1], 3000).into()).await
+/// }
+/// # }
+/// ```
+pub async fn serve_local(app: Router, addr: SocketAddr) -> Result<(), Error> {
+    Vercel::new().serve_local(app, addr).await
+}
+
+/// Serves an axum router on a local server like [`serve_local`], for a
+/// deployment with a custom [`Rewrite`].
+pub async fn serve_local_with(
+    app: Router,
+    addr: SocketAddr,
+    rewrite: Rewrite,
+) -> Result<(), Error> {
+    Vercel::new().rewrite(rewrite).serve_local(app, addr).await
+}
+
+/// Buffers a local request the way vercel does, and rewrites its uri.
+async fn local_request(
+    req: HttpRequest<AxumBody>,
+    rewritten: Result<Uri, TranslateError>,
+    vercel: &Vercel,
+) -> Result<HttpRequest<AxumBody>, TranslateError> {
+    let (mut parts, mut body) = req.into_parts();
+    parts.uri = original_uri(rewritten?.into_parts(), &vercel.rewrite)?;
+    let mut buffer = Vec::new();
+    while let Some(bytes) = body.data().await {
+        let bytes = bytes.map_err(|e| TranslateError::Body(e.to_string()))?;
+        buffer.extend(bytes);
+        check_body_size(buffer.len(), vercel)?;
+    }
+    Ok(HttpRequest::from_parts(parts, AxumBody::from(buffer)))
+}
+
+fn check_body_size(size: usize, vercel: &Vercel) -> Result<(), TranslateError> {
+    if size > vercel.max_body_size {
+        return Err(TranslateError::TooLarge {
+            size,
+            limit: vercel.max_body_size,
+        });
+    }
+    Ok(())
+}
+
+/// Why a request from vercel could not be turned back into the request that
+/// was made.
+#[derive(Debug)]
+enum TranslateError {
+    /// There is no path query parameter, so the request was not rewritten
+    /// by `vercel.json`, such as a request made to `/api/main` directly.
+    MissingPath(String),
+    /// The path and query before the rewrite are not valid in a uri.
+    InvalidPath(InvalidUri),
+    /// The uri could not be put back together.
+    InvalidUri(InvalidUriParts),
+    /// The body is larger than [`Vercel::max_body_size`].
+    TooLarge { size: usize, limit: usize },
+    /// The body could not be read.
+    Body(String),
+}
+impl TranslateError {
+    fn status(&self) -> StatusCode {
+        match self {
+            TranslateError::MissingPath(_) | TranslateError::InvalidPath(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            TranslateError::InvalidUri(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            TranslateError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            TranslateError::Body(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+impl fmt::Display for TranslateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TranslateError::MissingPath(param) => write!(
+                f,
+                "no `{param}` query parameter, is the rewrite in vercel.json set up?"
+            ),
+            TranslateError::InvalidPath(e) => write!(f, "invalid rewritten path: {e}"),
+            TranslateError::InvalidUri(e) => write!(f, "could not rebuild the uri: {e}"),
+            TranslateError::TooLarge { size, limit } => {
+                write!(
+                    f,
+                    "body of at least {size} bytes is over the {limit} byte limit"
+                )
+            }
+            TranslateError::Body(e) => write!(f, "could not read the body: {e}"),
+        }
+    }
+}
+impl error::Error for TranslateError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            TranslateError::MissingPath(_)
+            | TranslateError::TooLarge { .. }
+            | TranslateError::Body(_) => None,
+            TranslateError::InvalidPath(e) => Some(e),
+            TranslateError::InvalidUri(e) => Some(e),
+        }
+    }
+}
+impl From<TranslateError> for RequestError {
+    fn from(e: TranslateError) -> Self {
+        RequestError::new(e.status(), e)
+    }
+}
+impl IntoResponse for TranslateError {
+    fn into_response(self) -> AxumResponse {
+        (self.status(), self.status().to_string()).into_response()
+    }
+}
+
+/// Turns a request from vercel into the request that was made before the
+/// rewrite in `vercel.json`, for the axum router.
+fn translate_request(
+    req: Request,
+    vercel: &Vercel,
+) -> Result<HttpRequest<AxumBody>, TranslateError> {
+    let (mut parts, body) = req.into_parts();
+    parts.uri = original_uri(parts.uri.into_parts(), &vercel.rewrite)?;
+    let body = match body {
+        Body::Empty => AxumBody::empty(),
+        Body::Text(text) => {
+            check_body_size(text.len(), vercel)?;
+            AxumBody::from(text)
+        }
+        Body::Binary(bytes) => {
+            check_body_size(bytes.len(), vercel)?;
+            AxumBody::from(bytes)
         }
     };
-    vercel_run(handler).await
+    Ok(HttpRequest::from_parts(parts, body))
+}
+
+/// The body of a response, as text if its content type is textual so the
+/// runtime does not base64 encode it.
+fn response_body(headers: &HeaderMap, bytes: Vec<u8>) -> Body {
+    if bytes.is_empty() {
+        return Body::Empty;
+    }
+    let content_type = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if !is_text(content_type) {
+        return Body::Binary(bytes);
+    }
+    match String::from_utf8(bytes) {
+        Ok(text) => Body::Text(text),
+        Err(e) => Body::Binary(e.into_bytes()),
+    }
+}
+
+/// The uri vercel passes to the function for a request to `uri`, or `None`
+/// if the rewrite does not match it.
+fn vercel_rewrite(uri: &Uri, rewrite: &Rewrite) -> Option<Result<Uri, TranslateError>> {
+    let path = uri.path().strip_prefix(rewrite.base_path.as_str())?;
+    if !(path.is_empty() || path.starts_with('/')) {
+        return None;
+    }
+    // The path is already percent-encoded, so it goes into the query as it
+    // is, escaping only what form decoding would otherwise change
+    let value = path
+        .trim_start_matches('/')
+        .replace('+', "%2B")
+        .replace('&', "%26");
+    let param: String = form_urlencoded::byte_serialize(rewrite.param.as_bytes()).collect();
+    let query = match uri.query() {
+        Some(query) if !query.is_empty() => format!("{query}&{param}={value}"),
+        _ => format!("{param}={value}"),
+    };
+    let path_and_query = format!("{FUNCTION_PATH}?{query}")
+        .parse()
+        .map_err(TranslateError::InvalidPath);
+    Some(path_and_query.and_then(|path_and_query| {
+        let mut parts = uri.clone().into_parts();
+        parts.path_and_query = Some(path_and_query);
+        Uri::from_parts(parts).map_err(TranslateError::InvalidUri)
+    }))
+}
+
+/// The uri requested before the vercel rewrite, from the path query
+/// parameter. Vercel adds the parameter after any in the request, so if
+/// there are several the last is used, and the rest are kept as they were
+/// sent.
+fn original_uri(mut parts: uri::Parts, rewrite: &Rewrite) -> Result<Uri, TranslateError> {
+    let query = parts
+        .path_and_query
+        .as_ref()
+        .and_then(PathAndQuery::query)
+        .unwrap_or_default();
+
+    // Get supplied path from vercel rewrite, keeping the rest of the query
+    // exactly as it was encoded
+    let mut pairs: Vec<&str> = query.split('&').filter(|pair| !pair.is_empty()).collect();
+    let position = pairs
+        .iter()
+        .rposition(|pair| form_urlencoded::parse(pair.as_bytes()).any(|(k, _)| k == rewrite.param))
+        .ok_or_else(|| TranslateError::MissingPath(rewrite.param.clone()))?;
+    let path = pairs.remove(position);
+    let path = form_urlencoded::parse(path.as_bytes())
+        .next()
+        .map(|(_, v)| v)
+        .unwrap_or_default();
+
+    // Compute original path based on path uri, encoding it again as the
+    // parameter was decoded
+    let path = crate::route::encode_wildcard(&path.trim_start_matches('/'));
+    let mut new_path = format!("{}/{path}", rewrite.base_path);
+    if !pairs.is_empty() {
+        new_path.push('?');
+        new_path.push_str(&pairs.join("&"));
+    }
+    let new_path = new_path.parse().map_err(TranslateError::InvalidPath)?;
+
+    parts.path_and_query = Some(new_path);
+    Uri::from_parts(parts).map_err(TranslateError::InvalidUri)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(uri: &str) -> Request {
+        HttpRequest::builder()
+            .uri(uri)
+            .body(Body::Text("body".to_string()))
+            .unwrap()
+    }
+
+    fn translate(uri: &str, rewrite: &Rewrite) -> String {
+        original_uri(uri.parse::<Uri>().unwrap().into_parts(), rewrite)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn translates_rewritten_request() {
+        let req = translate_request(
+            request("https://example.com/api/main?path=hello%2Fworld"),
+            &Vercel::default(),
+        )
+        .unwrap();
+        assert_eq!(req.uri().host(), Some("example.com"));
+        assert_eq!(req.uri().path(), "/hello/world");
+    }
+
+    #[test]
+    fn strips_path_and_keeps_query() {
+        let rewrite = Rewrite::default();
+        assert_eq!(
+            translate("/api/main?b=x%20y&a=1+2&path=hello&c", &rewrite),
+            "/hello?b=x%20y&a=1+2&c"
+        );
+        assert_eq!(translate("/api/main?path=hello", &rewrite), "/hello");
+        assert_eq!(translate("/api/main?path=", &rewrite), "/");
+        // only the path added by vercel is removed
+        assert_eq!(
+            translate("/api/main?path=mine&path=hello", &rewrite),
+            "/hello?path=mine"
+        );
+    }
+
+    #[test]
+    fn custom_param_and_base_path() {
+        let rewrite = Rewrite::new().param("rest").base_path("/app/");
+        assert_eq!(
+            translate("/api/main?path=1&rest=todos%2F3", &rewrite),
+            "/app/todos/3?path=1"
+        );
+        assert_eq!(translate("/api/main?rest=", &rewrite), "/app/");
+    }
+
+    #[test]
+    fn missing_path_is_bad_request() {
+        let e = translate_request(
+            request("https://example.com/api/main?a=1"),
+            &Vercel::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(e, TranslateError::MissingPath(_)));
+        assert_eq!(e.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn encodes_decoded_paths() {
+        let req = translate_request(
+            request("https://example.com/api/main?path=a%20b"),
+            &Vercel::default(),
+        )
+        .unwrap();
+        assert_eq!(req.uri().path(), "/a%20b");
+        assert_eq!(
+            translate(
+                "/api/main?path=hello%2FFerris+Crab%2Fcaf%C3%A9&q=a%20b",
+                &Rewrite::default()
+            ),
+            "/hello/Ferris%20Crab/caf%C3%A9?q=a%20b"
+        );
+        assert_eq!(
+            translate("/api/main?path=100%25", &Rewrite::default()),
+            "/100%25"
+        );
+    }
+
+    #[test]
+    fn invalid_uri_is_server_error() {
+        let mut parts = uri::Parts::default();
+        parts.scheme = Some(uri::Scheme::HTTPS);
+        parts.path_and_query = Some(PathAndQuery::from_static("/api/main?path=a"));
+        let e = original_uri(parts, &Rewrite::default()).unwrap_err();
+        assert!(matches!(e, TranslateError::InvalidUri(_)));
+        assert_eq!(e.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn large_body_is_too_large() {
+        let vercel = Vercel::new().max_body_size(3);
+        let e = translate_request(request("/api/main?path="), &vercel).unwrap_err();
+        assert!(matches!(e, TranslateError::TooLarge { size: 4, limit: 3 }));
+        assert_eq!(e.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(translate_request(request("/api/main?path="), &Vercel::new()).is_ok());
+    }
+
+    #[test]
+    fn detects_text_bodies() {
+        let body = |content_type: &str, bytes: &[u8]| {
+            let mut headers = HeaderMap::new();
+            headers.insert(CONTENT_TYPE, content_type.parse().unwrap());
+            response_body(&headers, bytes.to_vec())
+        };
+        assert_eq!(
+            body("text/html; charset=utf-8", b"<p>Hi</p>"),
+            Body::Text("<p>Hi</p>".to_string())
+        );
+        assert_eq!(
+            body("application/ld+json", b"{}"),
+            Body::Text("{}".to_string())
+        );
+        assert_eq!(
+            body("image/png", b"\x89PNG"),
+            Body::Binary(b"\x89PNG".to_vec())
+        );
+        assert_eq!(body("text/plain", b"\xff"), Body::Binary(b"\xff".to_vec()));
+        assert_eq!(response_body(&HeaderMap::new(), Vec::new()), Body::Empty);
+    }
+
+    #[test]
+    fn handles_requests() {
+        let mut app = Router::new().route("/hello", axum::routing::get(|| async { "Hi" }));
+        let vercel = Vercel::new();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let mut handle =
+            |uri| runtime.block_on(serverless::handle(&vercel, &mut app, request(uri)));
+
+        let response = handle("https://example.com/api/main?path=hello");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body(), &Body::Text("Hi".to_string()));
+
+        let response = handle("https://example.com/api/main");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn emulates_vercel_rewrite() {
+        let rewrite = Rewrite::new().base_path("/app");
+        let uri: Uri = "/app/hello/world?a=1".parse().unwrap();
+        let rewritten = vercel_rewrite(&uri, &rewrite).unwrap().unwrap();
+        assert_eq!(rewritten.path(), "/api/main");
+        assert_eq!(
+            original_uri(rewritten.into_parts(), &rewrite)
+                .unwrap()
+                .to_string(),
+            "/app/hello/world?a=1"
+        );
+        assert!(vercel_rewrite(&"/application".parse().unwrap(), &rewrite).is_none());
+
+        for path in [
+            "/app/a%20b",
+            "/app/100%25",
+            "/app/caf%C3%A9",
+            "/app/a+b&c=d",
+        ] {
+            let uri: Uri = path.parse().unwrap();
+            let rewritten = vercel_rewrite(&uri, &rewrite).unwrap().unwrap();
+            assert_eq!(
+                original_uri(rewritten.into_parts(), &rewrite)
+                    .unwrap()
+                    .to_string(),
+                path
+            );
+        }
+    }
 }
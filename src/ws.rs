@@ -0,0 +1,249 @@
+//! WebSockets for the htmx
+//! [ws extension](https://htmx.org/extensions/web-sockets/), which sends forms
+//! as JSON messages and swaps the fragments it receives into the page by id.
+//!
+//! ```no_run
+//! # use axum::{extract::ws::{WebSocket, WebSocketUpgrade}, response::Response};
+//! use hyperide::{hx::SwapStyle, hyperide, oob::Oob, ws};
+//! #[derive(serde::Deserialize)]
+//! struct Chat {
+//!     message: String,
+//! }
+//! async fn chat(upgrade: WebSocketUpgrade) -> Response {
+//!     upgrade.on_upgrade(|mut socket: WebSocket| async move {
+//!         while let Some(Ok(chat)) = ws::recv::<Chat>(&mut socket).await {
+//!             let message = Oob::default().with_selector(
+//!                 SwapStyle::BeforeEnd,
+//!                 "#messages",
+//!                 hyperide! { <p>{chat.values.message}</p> },
+//!             );
+//!             if ws::send(&mut socket, message).await.is_err() {
+//!                 break;
+//!             }
+//!         }
+//!     })
+//! }
+//! ```
+
+use std::{error, fmt};
+
+use axum::extract::ws::{Message, WebSocket};
+use futures_util::{stream::SplitSink, SinkExt};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use tokio::sync::broadcast;
+
+/// The htmx request headers sent with every message, under `HEADERS`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct WsHeaders {
+    /// `HX-Request`, which htmx always sends as `true`.
+    #[serde(rename = "HX-Request", deserialize_with = "deserialize_true")]
+    pub request: bool,
+    /// `HX-Trigger`, the id of the element that sent the message.
+    #[serde(rename = "HX-Trigger")]
+    pub trigger: Option<String>,
+    /// `HX-Trigger-Name`, the name of the element that sent the message.
+    #[serde(rename = "HX-Trigger-Name")]
+    pub trigger_name: Option<String>,
+    /// `HX-Target`, the id of the target element.
+    #[serde(rename = "HX-Target")]
+    pub target: Option<String>,
+    /// `HX-Current-URL`, the url of the page.
+    #[serde(rename = "HX-Current-URL")]
+    pub current_url: Option<String>,
+}
+
+fn deserialize_true<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    // htmx sends header values as strings
+    Ok(
+        match Option::<serde_json::Value>::deserialize(deserializer)? {
+            Some(serde_json::Value::Bool(b)) => b,
+            Some(serde_json::Value::String(s)) => s == "true",
+            _ => false,
+        },
+    )
+}
+
+/// A message sent by an element with `ws-send`. `T` is the form values, which
+/// can be any deserializable struct, or a [`serde_json::Map`] to accept
+/// anything.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct WsMessage<T = serde_json::Map<String, serde_json::Value>> {
+    #[serde(rename = "HEADERS", default)]
+    pub headers: WsHeaders,
+    #[serde(flatten)]
+    pub values: T,
+}
+impl<T: DeserializeOwned> WsMessage<T> {
+    /// Parses the JSON text of a message.
+    pub fn from_json(json: &str) -> Result<WsMessage<T>, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// An error receiving or sending a message.
+#[derive(Debug)]
+pub enum Error {
+    /// The socket failed.
+    Socket(axum::Error),
+    /// A message was not the JSON htmx sends.
+    Json(serde_json::Error),
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Socket(e) => write!(f, "websocket error: {e}"),
+            Error::Json(e) => write!(f, "invalid htmx websocket message: {e}"),
+        }
+    }
+}
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Socket(e) => Some(e),
+            Error::Json(e) => Some(e),
+        }
+    }
+}
+
+/// Waits for the next message sent by htmx, skipping pings, pongs, and
+/// binary messages. Returns `None` once the socket is closed.
+pub async fn recv<T: DeserializeOwned>(
+    socket: &mut WebSocket,
+) -> Option<Result<WsMessage<T>, Error>> {
+    loop {
+        match socket.recv().await? {
+            Ok(Message::Text(json)) => {
+                return Some(WsMessage::from_json(&json).map_err(Error::Json))
+            }
+            Ok(Message::Close(_)) => return None,
+            Ok(_) => continue,
+            Err(e) => return Some(Err(Error::Socket(e))),
+        }
+    }
+}
+
+/// Sends a fragment, which htmx swaps into the page in place of the element
+/// with the same id, or as its `hx-swap-oob` attribute says. Build fragments
+/// which swap elsewhere with [`crate::oob::Oob`].
+pub async fn send(socket: &mut WebSocket, fragment: impl Into<String>) -> Result<(), Error> {
+    socket
+        .send(Message::Text(fragment.into()))
+        .await
+        .map_err(Error::Socket)
+}
+
+/// Sends fragments to every connected socket, such as a live feed shared by
+/// everyone viewing a page.
+///
+/// ```no_run
+/// # use axum::extract::ws::WebSocket;
+/// use futures_util::StreamExt;
+/// use hyperide::ws::Broadcast;
+/// async fn connect(feed: Broadcast, socket: WebSocket) {
+///     let (sender, _receiver) = socket.split();
+///     feed.forward(sender).await;
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Broadcast {
+    sender: broadcast::Sender<String>,
+}
+impl Broadcast {
+    /// A broadcast which holds up to `capacity` fragments for connections
+    /// that are slow to send them. Slow connections skip the oldest
+    /// fragments once it is full.
+    pub fn new(capacity: usize) -> Broadcast {
+        let (sender, _) = broadcast::channel(capacity);
+        Broadcast { sender }
+    }
+
+    /// Sends `fragment` to every connection, returning how many there are.
+    pub fn send(&self, fragment: impl Into<String>) -> usize {
+        self.sender.send(fragment.into()).unwrap_or(0)
+    }
+
+    /// The number of connections.
+    pub fn connections(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Sends every fragment broadcast from now on to `sender`, until the
+    /// socket closes.
+    pub async fn forward(&self, mut sender: SplitSink<WebSocket, Message>) {
+        let mut receiver = self.sender.subscribe();
+        loop {
+            let fragment = match receiver.recv().await {
+                Ok(fragment) => fragment,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return,
+            };
+            if sender.send(Message::Text(fragment)).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A message sent by the ws extension of htmx 1.9 from
+    /// `<form id="chat" ws-send><input name="message"></form>`.
+    const RECORDED: &str = r#"{
+        "message": "Hello",
+        "HEADERS": {
+            "HX-Request": "true",
+            "HX-Trigger": "chat",
+            "HX-Trigger-Name": null,
+            "HX-Target": "chat",
+            "HX-Current-URL": "http://localhost:3000/chat"
+        }
+    }"#;
+
+    #[derive(Debug, Deserialize)]
+    struct Chat {
+        message: String,
+    }
+
+    #[test]
+    fn parses_headers() {
+        let message = WsMessage::<Chat>::from_json(RECORDED).unwrap();
+        assert_eq!(
+            message.headers,
+            WsHeaders {
+                request: true,
+                trigger: Some("chat".to_string()),
+                trigger_name: None,
+                target: Some("chat".to_string()),
+                current_url: Some("http://localhost:3000/chat".to_string()),
+            }
+        );
+        assert_eq!(message.values.message, "Hello");
+    }
+
+    #[test]
+    fn leaves_headers_out_of_untyped_values() {
+        let message = WsMessage::<serde_json::Map<_, _>>::from_json(RECORDED).unwrap();
+        assert!(message.headers.request);
+        assert_eq!(message.values.len(), 1);
+        assert_eq!(message.values["message"], "Hello");
+    }
+
+    #[test]
+    fn accepts_missing_or_boolean_headers() {
+        let message = WsMessage::<Chat>::from_json(r#"{"message":"Hi"}"#).unwrap();
+        assert_eq!(message.headers, WsHeaders::default());
+        let json = r#"{"message":"Hi","HEADERS":{"HX-Request":true}}"#;
+        assert!(WsMessage::<Chat>::from_json(json).unwrap().headers.request);
+        let json = r#"{"message":"Hi","HEADERS":{"HX-Request":"false"}}"#;
+        assert!(!WsMessage::<Chat>::from_json(json).unwrap().headers.request);
+    }
+
+    #[test]
+    fn rejects_missing_values() {
+        let json = r#"{"HEADERS":{"HX-Request":"true"}}"#;
+        assert!(WsMessage::<Chat>::from_json(json).is_err());
+    }
+}